@@ -1,15 +1,31 @@
+import { useEffect } from 'react';
 import { useDispatch, useSelector } from 'react-redux';
-import { startAuth, handleAuth, logoutUser, clearError } from '../store/slices/authSlice';
+import { startAuth, handleAuth, logoutUser, clearError, authCompleted, sessionExpired } from '../store/slices/authSlice';
+import { onIpcEvent, IPC_EVENTS } from '../utils/ipc';
 
 export function useAuth() {
   const dispatch = useDispatch();
   const { user, status, error } = useSelector(state => state.auth);
 
+  useEffect(() => {
+    const unsubscribers = [
+      onIpcEvent(IPC_EVENTS.AUTH_COMPLETED, (event) => {
+        dispatch(authCompleted({ user: event.user, error: event.error }));
+      }),
+      onIpcEvent(IPC_EVENTS.RELOGIN_REQUIRED, (event) => {
+        dispatch(sessionExpired(event.reason));
+      }),
+    ];
+    return () => unsubscribers.forEach((unsubscribe) => unsubscribe());
+  }, [dispatch]);
+
+  // Resolves to { loopback } so the caller knows whether a code has to be pasted
   const login = async () => {
     try {
-      await dispatch(startAuth()).unwrap();
+      return await dispatch(startAuth()).unwrap();
     } catch (err) {
       console.error('Login failed:', err);
+      return { loopback: false };
     }
   };
 
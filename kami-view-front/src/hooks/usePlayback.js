@@ -30,10 +30,10 @@ export function usePlayback() {
     }
   }, [dispatch, currentEpisode]);
 
-  const handlePlay = async (shikimoriId, episode, translationId) => {
+  const handlePlay = async (shikimoriId, episode, translationId, mediaId) => {
     try {
-      await dispatch(playEpisode({ shikimoriId, episode, translationId })).unwrap();
-      dispatch(setCurrentEpisode({ shikimoriId, episode, translationId }));
+      await dispatch(playEpisode({ mediaId, shikimoriId, episode, translationId })).unwrap();
+      dispatch(setCurrentEpisode({ mediaId, shikimoriId, episode, translationId }));
     } catch (err) {
       console.error('Failed to play episode:', err);
     }
@@ -21,7 +21,7 @@ export function AnimeDetails({ id }) {
 
   const handlePlay = (episodeNumber) => {
     if (!selectedTranslation) return;
-    playEpisode(id, episodeNumber, selectedTranslation.id);
+    playEpisode(id, episodeNumber, selectedTranslation.id, selectedAnime?.mediaId);
   };
 
   const badgeVariants = {
@@ -128,6 +128,11 @@ export function AnimeDetails({ id }) {
                           <span className="px-1.5 py-0.5 text-xs rounded-full bg-light-card-hover dark:bg-dark-card-hover">
                             {translation.episodes} ep.
                           </span>
+                          {translation.kind === 'subtitles' && (
+                            <span className="px-1.5 py-0.5 text-xs rounded-full bg-light-card-hover dark:bg-dark-card-hover">
+                              sub
+                            </span>
+                          )}
                           {selectedTranslation?.id === translation.id && (
                             <motion.i
                               initial={{ scale: 0 }}
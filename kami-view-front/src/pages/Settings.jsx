@@ -15,8 +15,8 @@ export function Settings() {
   const [showCodeInput, setShowCodeInput] = useState(false);
 
   const handleLogin = async () => {
-    await login();
-    setShowCodeInput(true); // Show input after opening auth URL
+    const { loopback } = await login();
+    setShowCodeInput(!loopback); // The code is only pasted without the loopback redirect
   };
 
   const handleSubmitCode = async () => {
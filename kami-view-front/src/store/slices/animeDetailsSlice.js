@@ -6,6 +6,7 @@ export const selectAnime = createAsyncThunk(
   async (anime, { rejectWithValue }) => {
     try {
       const response = await sendIpcMessage(IPC_TYPES.ANIME_SELECTED, {
+        mediaId: anime.mediaId,
         shikimoriId: anime.id
       });
 
@@ -18,7 +19,8 @@ export const selectAnime = createAsyncThunk(
         translations: response.data.translations.map(t => ({
           id: t.id,
           title: t.title,
-          episodes: t.episodes
+          episodes: t.episodes,
+          kind: t.kind
         })),
         episodeCount: response.data.episodes
       };
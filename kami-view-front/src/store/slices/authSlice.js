@@ -10,8 +10,15 @@ const initialState = {
 export const startAuth = createAsyncThunk(
   'auth/startAuth',
   async () => {
-    const response = await sendIpcMessage(IPC_TYPES.OPEN_AUTH_URL);
-    return response;
+    // Prefer the loopback redirect, fall back to pasting the code by hand
+    try {
+      await sendIpcMessage(IPC_TYPES.START_LOOPBACK_LOGIN);
+      return { loopback: true };
+    } catch (err) {
+      console.warn('Loopback login unavailable:', err.message);
+    }
+    await sendIpcMessage(IPC_TYPES.OPEN_AUTH_URL);
+    return { loopback: false };
   }
 );
 
@@ -36,6 +43,22 @@ const authSlice = createSlice({
   reducers: {
     clearError: (state) => {
       state.error = null;
+    },
+    // Result of the loopback login pushed by the backend
+    authCompleted: (state, action) => {
+      if (action.payload.user) {
+        state.status = 'succeeded';
+        state.user = action.payload.user;
+      } else {
+        state.status = 'failed';
+        state.error = action.payload.error;
+      }
+    },
+    // The refresh token was revoked, the backend already dropped the tokens
+    sessionExpired: (state, action) => {
+      state.user = null;
+      state.status = 'idle';
+      state.error = action.payload;
     }
   },
   extraReducers: (builder) => {
@@ -44,8 +67,9 @@ const authSlice = createSlice({
       .addCase(startAuth.pending, (state) => {
         state.status = 'loading';
       })
-      .addCase(startAuth.fulfilled, (state) => {
-        state.status = 'idle';
+      .addCase(startAuth.fulfilled, (state, action) => {
+        // Loopback login keeps loading until authCompleted arrives
+        state.status = action.payload.loopback ? 'loading' : 'idle';
       })
       .addCase(startAuth.rejected, (state, action) => {
         state.status = 'failed';
@@ -75,5 +99,5 @@ const authSlice = createSlice({
   }
 });
 
-export const { clearError } = authSlice.actions;
+export const { clearError, authCompleted, sessionExpired } = authSlice.actions;
 export default authSlice.reducer; 
\ No newline at end of file
@@ -3,9 +3,10 @@ import { sendIpcMessage, IPC_TYPES } from '../../utils/ipc';
 
 export const playEpisode = createAsyncThunk(
   'playback/playEpisode',
-  async ({ shikimoriId, episode, translationId }, { rejectWithValue }) => {
+  async ({ mediaId, shikimoriId, episode, translationId }, { rejectWithValue }) => {
     try {
       const response = await sendIpcMessage(IPC_TYPES.PLAY_EPISODE, {
+        mediaId,
         shikimoriId,
         episode,
         translationId
@@ -14,11 +14,17 @@ export const searchAnime = createAsyncThunk(
 
       return response.data.results.map(item => ({
         id: item.shikimori_id || item.kinopoisk_id || item.imdb_id,
-        title: item.material_data.title,
-        image: item.material_data.poster_url,
-        rating: item.material_data.imdb_rating || item.material_data.kinopoisk_rating,
-        year: item.material_data.year,
-        description: item.material_data.description
+        mediaId: item.media_id,
+        title: item.title,
+        image: item.poster,
+        rating: item.score,
+        year: item.year,
+        description: item.description,
+        genres: item.genres,
+        status: item.status,
+        episodesAired: item.episodes_aired,
+        episodesTotal: item.episodes_total,
+        translations: item.translations
       }));
     } catch (err) {
       return rejectWithValue(err.message);
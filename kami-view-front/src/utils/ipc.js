@@ -1,6 +1,7 @@
 // IPC message types
 export const IPC_TYPES = {
   SEARCH: 'search',
+  BROWSE: 'browse',
   ANIME_SELECTED: 'animeSelected',
   PLAY_EPISODE: 'playEpisode',
   GET_PLAYBACK_INFO: 'getPlaybackInfo',
@@ -8,9 +9,47 @@ export const IPC_TYPES = {
   STOP_PLAYBACK: 'stopPlayback',
   START_DOWNLOAD: 'startDownload',
   OPEN_AUTH_URL: 'openAuthUrl',
+  START_LOOPBACK_LOGIN: 'startLoopbackLogin',
   EXCHANGE_CODE: 'exchangeCode',
   GET_USER_INFO: 'getUserInfo',
   LOGOUT: 'logout',
+  UPDATE_SETTINGS: 'updateSettings',
+  GET_KODIK_STATUS: 'getKodikStatus',
+  GET_ANIME_DETAILS: 'getAnimeDetails',
+  CREATE_PLAYLIST: 'createPlaylist',
+  CANCEL_PLAYLIST: 'cancelPlaylist',
+  EXPORT_PLAYLIST: 'exportPlaylist',
+  FOLLOW_TITLE: 'followTitle',
+  UNFOLLOW_TITLE: 'unfollowTitle',
+  GET_FOLLOWS: 'getFollows',
+  CHECK_FOLLOWS: 'checkFollows',
+  MARK_FOLLOW_UPDATES_SEEN: 'markFollowUpdatesSeen',
+  GET_USER_RATES: 'getUserRates',
+  SYNC_USER_RATES: 'syncUserRates',
+  UPDATE_USER_RATE: 'updateUserRate',
+};
+
+// Events pushed by the backend without a request
+export const IPC_EVENTS = {
+  PLAYLIST_PROGRESS: 'playlistProgress',
+  PLAYLIST_READY: 'playlistReady',
+  FOLLOW_UPDATES: 'followUpdates',
+  USER_RATES_SYNCED: 'userRatesSynced',
+  EPISODE_SCROBBLED: 'episodeScrobbled',
+  AUTH_COMPLETED: 'authCompleted',
+  RELOGIN_REQUIRED: 'reloginRequired',
+};
+
+// Subscribe to a backend event, returns an unsubscribe function
+export const onIpcEvent = (type, handler) => {
+  const listener = (event) => {
+    if (event.detail && event.detail.type === type) {
+      handler(event.detail);
+    }
+  };
+
+  window.addEventListener('ipc-event', listener);
+  return () => window.removeEventListener('ipc-event', listener);
 };
 
 // Send IPC message and wait for response
@@ -87,5 +126,6 @@ export const RESPONSE_TYPES = {
   SUCCESS: 'success',
   ERROR: 'error',
   SEARCH_RESULTS: 'searchResults',
+  BROWSE_RESULTS: 'browseResults',
   ANIME_INFO: 'animeInfo'
 }; 
\ No newline at end of file
@@ -12,8 +12,8 @@ use crate::shikimori::{
     ShikimoriOAuthParameters
 };
 use crate::mpv::{MpvClient, MpvClientParameters};
-use crate::storage::{Storage, StorageParameters};
-use crate::di::interfaces::{IKodik, IShikimoriClient, IMpvClient, IStorage, IReqwestClient};
+use crate::storage::{FollowStore, FollowStoreParameters, Storage, StorageParameters};
+use crate::di::interfaces::{IKodik, IShikimoriClient, IMpvClient, IStorage, IFollowStore, IReqwestClient};
 use std::sync::{Arc, Mutex};
 use crate::client::ReqwestClient;
 use crate::utils::constants::CONFIG;
@@ -31,6 +31,7 @@ module! {
             ShikimoriOAuthComponent,
             MpvClient, 
             Storage, 
+            FollowStore,
             ReqwestClient
         ],
         providers = []
@@ -64,14 +65,21 @@ impl Container {
                 base_url: "https://shikimori.one/api".to_string(),
                 client_id: env.shikimoriclientid.clone(),
                 client_secret: env.shikimoriclientsecret.clone(),
+                http: Arc::new(reqwest::Client::new()),
+                rates: Arc::new(crate::shikimori::RatesStore::default()),
             })
             .with_component_parameters::<MpvClient>(MpvClientParameters {
                 socket_path: CONFIG.mpv_socket_path.to_string(),
                 sender: Arc::new(Mutex::new(None)),
+                events: tokio::sync::broadcast::channel(16).0,
             })
             .with_component_parameters::<Storage>(StorageParameters {
                 path: Storage::initialize_path(),
             })
+            .with_component_parameters::<FollowStore>(FollowStoreParameters {
+                path: FollowStore::initialize_path(),
+                lock: parking_lot::Mutex::new(()),
+            })
             .build()
     }
 
@@ -107,6 +115,14 @@ impl Container {
         self.resolve_ref()
     }
 
+    /// Получает хранилище отслеживаемых тайтлов.
+    ///
+    /// # Возвращает
+    /// * `&dyn IFollowStore` - Ссылка на хранилище подписок
+    pub fn follows(&self) -> &dyn IFollowStore {
+        self.resolve_ref()
+    }
+
     /// Получает интерфейс HTTP клиента.
     ///
     /// # Возвращает
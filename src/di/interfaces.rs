@@ -1,35 +1,69 @@
 use shaku::Interface;
 use anyhow::Result;
 use serde::Serialize;
-use crate::kodik::{MediaResult, InfoResponse, Translation};
+use crate::kodik::{AvailableTranslation, MediaResult, MediaId, SearchQuery, BrowseMode, BrowsePage, InfoResponse, Translation, StreamInfo, TokenStatus, SelfTestReport, Playlist, PlaylistProgress};
+use tokio::sync::broadcast;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio_util::sync::CancellationToken;
 use std::future::Future;
 use std::pin::Pin;
-use crate::storage::AppSettings;
+use crate::storage::{AppSettings, FollowedTitle, FollowUpdate, FollowsData};
+use crate::hls::MasterPlaylist;
 use reqwest::Response;
-use crate::shikimori::{UserInfo, TokenResponse};
+use crate::shikimori::{AnimeDetails, RateChange, RatesData, SyncReport, UserInfo, UserRate, TokenResponse};
 
 pub trait IKodikSearch: Interface {
-    fn search_anime<'a>(&'a self, query: &'a str) -> Pin<Box<dyn Future<Output = Result<Vec<MediaResult>>> + Send + 'a>>;
+    fn search_anime<'a>(&'a self, query: &'a SearchQuery) -> Pin<Box<dyn Future<Output = Result<Vec<MediaResult>>> + Send + 'a>>;
+    fn browse_anime<'a>(&'a self, mode: BrowseMode, cursor: Option<&'a str>) -> Pin<Box<dyn Future<Output = Result<BrowsePage>> + Send + 'a>>;
 }
 
 pub trait IKodikInfo: Interface {
-    fn get_anime_info<'a>(&'a self, shikimori_id: &'a str) -> Pin<Box<dyn Future<Output = Result<InfoResponse>> + Send + 'a>>;
-    fn get_translations<'a>(&'a self, shikimori_id: &'a str) -> Pin<Box<dyn Future<Output = Result<Vec<Translation>>> + Send + 'a>>;
-    fn get_series_count<'a>(&'a self, shikimori_id: &'a str) -> Pin<Box<dyn Future<Output = Result<i32>> + Send + 'a>>;
+    fn get_anime_info<'a>(&'a self, id: &'a MediaId) -> Pin<Box<dyn Future<Output = Result<InfoResponse>> + Send + 'a>>;
+    fn get_translations<'a>(&'a self, id: &'a MediaId) -> Pin<Box<dyn Future<Output = Result<Vec<Translation>>> + Send + 'a>>;
+    fn get_series_count<'a>(&'a self, id: &'a MediaId) -> Pin<Box<dyn Future<Output = Result<i32>> + Send + 'a>>;
+    /// Translations with their current episode counts, fetched without the cache
+    fn get_available_translations<'a>(&'a self, id: &'a MediaId) -> Pin<Box<dyn Future<Output = Result<Vec<AvailableTranslation>>> + Send + 'a>>;
 }
 
 pub trait IKodikPlayback: Interface {
-    fn get_episode_link<'a>(&'a self, shikimori_id: &'a str, episode: i32, translation_id: &'a str) -> Pin<Box<dyn Future<Output = Result<(String, i32)>> + Send + 'a>>;
-    fn create_playlist<'a>(&'a self, title: &'a str, shikimori_id: &'a str, translation_id: &'a str) -> Pin<Box<dyn Future<Output = Result<String>> + Send + 'a>>;
+    fn get_episode_link<'a>(&'a self, id: &'a MediaId, season: i32, episode: i32, translation_id: &'a str) -> Pin<Box<dyn Future<Output = Result<StreamInfo>> + Send + 'a>>;
+    /// Same as `get_episode_link`, but skips cached links (e.g. after the CDN rejected one)
+    fn refresh_episode_link<'a>(&'a self, id: &'a MediaId, season: i32, episode: i32, translation_id: &'a str) -> Pin<Box<dyn Future<Output = Result<StreamInfo>> + Send + 'a>>;
+    /// Resolve an episode ahead of time so `get_episode_link` is served from the cache;
+    /// `refresh` re-resolves links that are already cached
+    fn prefetch_episode<'a>(&'a self, id: &'a MediaId, season: i32, episode: i32, translation_id: &'a str, quality: Option<i32>, refresh: bool) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>>;
+    fn get_hls_variants<'a>(&'a self, hls_url: &'a str) -> Pin<Box<dyn Future<Output = Result<MasterPlaylist>> + Send + 'a>>;
+    fn create_playlist<'a>(
+        &'a self,
+        title: &'a str,
+        id: &'a MediaId,
+        translation_id: &'a str,
+        quality: Option<i32>,
+        progress: Option<UnboundedSender<PlaylistProgress>>,
+        cancel: CancellationToken,
+    ) -> Pin<Box<dyn Future<Output = Result<Playlist>> + Send + 'a>>;
 }
 
-pub trait IKodik: IKodikSearch + IKodikInfo + IKodikPlayback + Interface {}
+pub trait IKodik: IKodikSearch + IKodikInfo + IKodikPlayback + Interface {
+    fn token_status<'a>(&'a self) -> Pin<Box<dyn Future<Output = Option<TokenStatus>> + Send + 'a>>;
+    fn self_test<'a>(&'a self, id: &'a MediaId) -> Pin<Box<dyn Future<Output = SelfTestReport> + Send + 'a>>;
+}
 
 pub trait IShikimoriOAuth: Interface {
     fn get_user_info(&self) -> Pin<Box<dyn Future<Output = Result<UserInfo>> + Send>>;
     fn logout(&self) -> Pin<Box<dyn Future<Output = Result<()>> + Send>>;
     fn exchange_code(&self, code: &str) -> Pin<Box<dyn Future<Output = Result<TokenResponse>> + Send>>;
     fn get_auth_url(&self) -> Result<String>;
+    /// Log in through a redirect to a listener on 127.0.0.1:`port`
+    fn login_loopback(&self, port: u16) -> Pin<Box<dyn Future<Output = Result<TokenResponse>> + Send>>;
+    /// Access token for an API call, refreshed first when close to expiry
+    fn access_token(&self) -> Pin<Box<dyn Future<Output = Result<String>> + Send>>;
+    /// New access token after `rejected` got a 401; concurrent refreshes are merged
+    fn refresh_token(&self, rejected: &str) -> Pin<Box<dyn Future<Output = Result<String>> + Send>>;
+    fn is_logged_in(&self) -> Pin<Box<dyn Future<Output = bool> + Send>>;
+    /// Refresh tokens ahead of expiry; runs until the app exits
+    fn keep_tokens_fresh(&self) -> Pin<Box<dyn Future<Output = ()> + Send>>;
+    fn subscribe_auth_events(&self) -> broadcast::Receiver<AuthEvent>;
 }
 
 pub trait IShikimoriClient: Interface {
@@ -37,11 +71,53 @@ pub trait IShikimoriClient: Interface {
     fn logout(&self) -> Pin<Box<dyn Future<Output = Result<()>> + Send>>;
     fn exchange_code(&self, code: &str) -> Pin<Box<dyn Future<Output = Result<TokenResponse>> + Send>>;
     fn get_auth_url(&self) -> Result<String>;
+    /// Log in through a redirect to a listener on 127.0.0.1:`port`
+    fn login_loopback(&self, port: u16) -> Pin<Box<dyn Future<Output = Result<TokenResponse>> + Send>>;
+    /// Anime details from `/animes/:id` by Shikimori id
+    fn get_anime(&self, id: i64) -> Pin<Box<dyn Future<Output = Result<AnimeDetails>> + Send>>;
+    fn is_logged_in(&self) -> Pin<Box<dyn Future<Output = bool> + Send>>;
+    /// Refresh tokens ahead of expiry; runs until the app exits
+    fn keep_tokens_fresh(&self) -> Pin<Box<dyn Future<Output = ()> + Send>>;
+    /// Session events, e.g. a revoked refresh token
+    fn subscribe_auth_events(&self) -> broadcast::Receiver<AuthEvent>;
+    /// Local copy of the user's anime list with queued edits applied
+    fn get_user_rates(&self) -> Result<RatesData>;
+    /// Push queued edits to `/v2/user_rates` and download the list
+    fn sync_user_rates(&self) -> Pin<Box<dyn Future<Output = Result<SyncReport>> + Send>>;
+    /// Change a rate locally and push it, queueing it while offline
+    fn update_user_rate(&self, change: RateChange) -> Pin<Box<dyn Future<Output = Result<UserRate>> + Send>>;
 }
 
 pub trait IMpvClient: Interface {
-    fn play(&self, url: &str) -> anyhow::Result<()>;
+    /// Play `url` from `start` seconds (or the beginning)
+    fn play(&self, url: &str, start: Option<f64>) -> anyhow::Result<()>;
     fn get_playback_info(&self) -> anyhow::Result<PlaybackInfo>;
+    /// Events reported by the running mpv instance
+    fn subscribe_events(&self) -> broadcast::Receiver<MpvEvent>;
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum MpvEvent {
+    /// The stream stopped loading mid-playback, usually an expired signed link
+    StreamFailed {
+        position: f64,
+        reason: String,
+    },
+    /// Periodic playback position, in seconds
+    Progress {
+        position: f64,
+        duration: f64,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum AuthEvent {
+    /// The refresh token was revoked or has expired; tokens are already cleared
+    ReloginRequired {
+        reason: String,
+    },
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -56,6 +132,16 @@ pub trait IStorage: Interface {
     fn save(&self, settings: &AppSettings) -> Result<(), Box<dyn std::error::Error>>;
 }
 
+pub trait IFollowStore: Interface {
+    fn load(&self) -> Result<FollowsData>;
+    /// Start following `id`, remembering the current episode count of each translation
+    fn follow(&self, id: MediaId, title: String, translations: &[AvailableTranslation]) -> Result<FollowedTitle>;
+    fn unfollow(&self, id: &MediaId) -> Result<bool>;
+    /// Store a fresh check of `id` and return translations that gained episodes
+    fn record(&self, id: &MediaId, translations: &[AvailableTranslation]) -> Result<Vec<FollowUpdate>>;
+    fn mark_seen(&self) -> Result<()>;
+}
+
 pub trait IReqwestClient: Interface {
     fn get<'a>(&'a self, url: &'a str) -> Pin<Box<dyn Future<Output = Result<String>> + Send + 'a>>;
     fn get_bytes<'a>(&'a self, url: &'a str) -> Pin<Box<dyn Future<Output = Result<Vec<u8>>> + Send + 'a>>;
@@ -0,0 +1,24 @@
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use crate::di::Container;
+use crate::di::interfaces::AuthEvent;
+use crate::gui::backend::events::EventEmitter;
+use crate::gui::backend::ipc::IpcEvent;
+
+/// Keep Shikimori tokens fresh and tell the UI when the session can't be renewed
+pub fn spawn_token_refresher(container: Arc<Container>, emitter: EventEmitter) {
+    tokio::spawn(container.shikimori().keep_tokens_fresh());
+
+    let mut auth_events = container.shikimori().subscribe_auth_events();
+    tokio::spawn(async move {
+        loop {
+            match auth_events.recv().await {
+                Ok(AuthEvent::ReloginRequired { reason }) => {
+                    emitter.emit(IpcEvent::ReloginRequired { reason }).await;
+                },
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
@@ -0,0 +1,30 @@
+use tokio::sync::mpsc::Sender;
+use log::error;
+use crate::gui::backend::ipc::IpcEvent;
+
+/// Pushes backend-initiated events to the webview, outside of the request/response flow
+#[derive(Clone)]
+pub struct EventEmitter {
+    script_tx: Sender<(String, String)>
+}
+
+impl EventEmitter {
+    pub fn new(script_tx: Sender<(String, String)>) -> Self {
+        Self { script_tx }
+    }
+
+    pub async fn emit(&self, event: IpcEvent) {
+        let event_json = match serde_json::to_string(&event) {
+            Ok(json) => json,
+            Err(e) => {
+                error!("Failed to serialize IPC event: {}", e);
+                return;
+            }
+        };
+
+        let js_code = format!("window.__IPC_EVENT__({})", event_json);
+        if let Err(e) = self.script_tx.send(("event".to_string(), js_code)).await {
+            error!("Failed to send IPC event: {}", e);
+        }
+    }
+}
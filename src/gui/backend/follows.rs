@@ -0,0 +1,65 @@
+use std::sync::Arc;
+use std::time::Duration;
+use log::{debug, info, warn};
+use crate::di::Container;
+use crate::gui::backend::events::EventEmitter;
+use crate::gui::backend::ipc::IpcEvent;
+use crate::storage::FollowUpdate;
+
+/// How often followed titles are checked for new episodes
+const FOLLOWS_CHECK_INTERVAL: Duration = Duration::from_secs(30 * 60);
+/// Delay before the first check, so it doesn't compete with app startup
+const FOLLOWS_FIRST_CHECK_DELAY: Duration = Duration::from_secs(30);
+
+/// Query Kodik for every followed title and store the episode count diffs
+pub async fn check_followed_titles(container: &Arc<Container>) -> Vec<FollowUpdate> {
+    let titles = match container.follows().load() {
+        Ok(follows) => follows.titles,
+        Err(e) => {
+            warn!("Failed to load followed titles: {}", e);
+            return Vec::new();
+        }
+    };
+    debug!("Checking {} followed title(s) for new episodes", titles.len());
+
+    let mut updates = Vec::new();
+    for title in titles {
+        let translations = match container.kodik().get_available_translations(&title.id).await {
+            Ok(translations) => translations,
+            Err(e) => {
+                warn!("Failed to check followed title {}: {}", title.id, e);
+                continue;
+            }
+        };
+
+        match container.follows().record(&title.id, &translations) {
+            Ok(found) => updates.extend(found),
+            Err(e) => warn!("Failed to store follow check for {}: {}", title.id, e),
+        }
+    }
+    updates
+}
+
+/// Periodically check followed titles and push new episodes to the UI
+pub fn spawn_follows_checker(container: Arc<Container>, emitter: EventEmitter) {
+    tokio::spawn(async move {
+        tokio::time::sleep(FOLLOWS_FIRST_CHECK_DELAY).await;
+        let mut interval = tokio::time::interval(FOLLOWS_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let updates = check_followed_titles(&container).await;
+            if updates.is_empty() {
+                continue;
+            }
+
+            for update in &updates {
+                info!(
+                    "New episode of {} in {}: {} -> {}",
+                    update.title, update.translation_title, update.previous_episodes, update.episodes
+                );
+            }
+            emitter.emit(IpcEvent::FollowUpdates { updates }).await;
+        }
+    });
+}
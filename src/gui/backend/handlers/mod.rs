@@ -1,10 +1,18 @@
 use crate::di::Container;
+use crate::di::interfaces::MpvEvent;
 use std::sync::Arc;
 use serde_json::Value;
-use tokio::sync::Mutex;
-use crate::gui::backend::ipc::{IpcResponse, TranslationInfo};
-use crate::gui::backend::types::CurrentEpisode;
-use log::{error, debug};
+use tokio::sync::{broadcast, Mutex};
+use crate::gui::backend::ipc::{IpcEvent, IpcResponse, SearchResultItem, TranslationInfo};
+use crate::gui::backend::types::{CurrentEpisode, PlaylistJobs};
+use crate::gui::backend::events::EventEmitter;
+use crate::gui::backend::follows::check_followed_titles;
+use tokio_util::sync::CancellationToken;
+use crate::kodik::{parsing, BrowseMode, MediaId, Playlist, PlaylistFormat, SearchQuery, StreamQuality};
+use crate::hls::VariantPreference;
+use crate::shikimori::{RateChange, RateStatus};
+use crate::utils::constants::CONFIG;
+use log::{error, debug, info, warn};
 use anyhow::Result;
 use regex::Regex;
 use open;
@@ -31,13 +39,37 @@ fn extract_i64_field(payload: &Value, field: &str) -> Result<i64> {
         .ok_or_else(|| anyhow::anyhow!("Missing {} field", field))
 }
 
+// Helper function to extract media id from payload.
+// Accepts typed `mediaId: {type, id}` as well as legacy flat id fields.
+fn extract_media_id(payload: &Value) -> Result<MediaId> {
+    if let Some(media_id) = payload.get("mediaId").filter(|v| !v.is_null()) {
+        return serde_json::from_value(media_id.clone())
+            .map_err(|e| anyhow::anyhow!("Invalid mediaId field: {}", e));
+    }
+
+    let legacy_fields = [
+        ("shikimoriId", "shikimori"),
+        ("kinopoiskId", "kinopoisk"),
+        ("imdbId", "imdb"),
+        ("worldartLink", "worldart"),
+    ];
+
+    for (field, id_type) in legacy_fields {
+        if let Ok(id) = extract_str_field(payload, field) {
+            return Ok(MediaId::new(id_type, id)?);
+        }
+    }
+
+    Err(anyhow::anyhow!("Missing mediaId field"))
+}
+
 pub async fn handle_search(
     container: &Arc<Container>, 
     payload: Value
 ) -> Option<IpcResponse> {
     debug!("Handling search request with payload: {:?}", payload);
     
-    let query = match extract_str_field(&payload, "query") {
+    let query: SearchQuery = match serde_json::from_value(payload) {
         Ok(q) => q,
         Err(e) => {
             error!("Search error: {}", e);
@@ -46,11 +78,20 @@ pub async fn handle_search(
             });
         }
     };
+
+    if query.title.trim().is_empty() {
+        error!("Search error: empty query");
+        return Some(IpcResponse::Error {
+            message: "Invalid search request: Missing query field".to_string()
+        });
+    }
     
-    match container.kodik().search_anime(query).await {
+    match container.kodik().search_anime(&query).await {
         Ok(results) => {
-            debug!("Found {} results for query: {}", results.len(), query);
-            Some(IpcResponse::SearchResults { results })
+            debug!("Found {} results for query: {:?}", results.len(), query);
+            Some(IpcResponse::SearchResults {
+                results: results.into_iter().map(SearchResultItem::from).collect()
+            })
         },
         Err(e) => {
             error!("Search failed: {}", e);
@@ -61,13 +102,48 @@ pub async fn handle_search(
     }
 }
 
+pub async fn handle_browse(
+    container: &Arc<Container>,
+    payload: Value
+) -> Option<IpcResponse> {
+    debug!("Handling browse request with payload: {:?}", payload);
+
+    let mode: BrowseMode = match payload.get("mode").cloned().map(serde_json::from_value) {
+        Some(Ok(mode)) => mode,
+        Some(Err(e)) => {
+            error!("Browse error: {}", e);
+            return Some(IpcResponse::Error {
+                message: format!("Invalid browse mode: {}", e)
+            });
+        },
+        None => BrowseMode::RecentlyUpdated
+    };
+    let cursor = extract_str_field(&payload, "cursor").ok();
+
+    match container.kodik().browse_anime(mode, cursor).await {
+        Ok(page) => {
+            debug!("Browse {:?}: {} results", mode, page.results.len());
+            Some(IpcResponse::BrowseResults {
+                results: page.results.into_iter().map(SearchResultItem::from).collect(),
+                next_cursor: page.next_cursor
+            })
+        },
+        Err(e) => {
+            error!("Browse failed: {}", e);
+            Some(IpcResponse::Error {
+                message: format!("Browse failed: {}", e)
+            })
+        }
+    }
+}
+
 pub async fn handle_anime_selected(
     container: &Arc<Container>,
     payload: Value
 ) -> Option<IpcResponse> {
     debug!("Handling anime_selected request: {:?}", payload);
 
-    let shikimori_id = match extract_str_field(&payload, "shikimoriId") {
+    let media_id = match extract_media_id(&payload) {
         Ok(id) => id,
         Err(e) => {
             error!("Anime selection error: {}", e);
@@ -77,30 +153,39 @@ pub async fn handle_anime_selected(
         }
     };
     
-    match container.kodik().get_anime_info(shikimori_id).await {
+    match container.kodik().get_anime_info(&media_id).await {
         Ok(info) => {
-            // Parse translations with episode count
+            // Split "Name (N эп.)" into title and episode count; names without
+            // a count (movies, single translations) fall back to the series count
             let re = Regex::new(r"(.*?)\s*\((\d+)\s*эп\.\)").unwrap();
             let translations: Vec<TranslationInfo> = info.translations
                 .into_iter()
-                .filter_map(|t| {
-                    if let Some(caps) = re.captures(&t.name) {
-                        Some(TranslationInfo {
-                            id: t.id,
-                            title: caps[1].trim().to_string(),
-                            episodes: caps[2].parse().unwrap_or(0)
-                        })
-                    } else {
-                        None
+                .map(|t| {
+                    let (title, episodes) = match re.captures(&t.name) {
+                        Some(caps) => (caps[1].trim().to_string(), caps[2].parse().unwrap_or(0)),
+                        None => (t.name.trim().to_string(), info.series_count)
+                    };
+                    TranslationInfo {
+                        id: t.id,
+                        title,
+                        episodes,
+                        kind: t.translation_type
                     }
                 })
                 .collect();
                 
             debug!("Found anime info: {} translations", translations.len());
+            for season in &info.seasons {
+                let missing = season.missing_episodes();
+                if !missing.is_empty() {
+                    debug!("Season {} is missing episodes {:?}", season.number, missing);
+                }
+            }
             
             Some(IpcResponse::AnimeInfo {
                 translations,
-                episodes: info.series_count
+                episodes: info.series_count,
+                seasons: info.seasons
             })
         },
         Err(e) => {
@@ -112,6 +197,79 @@ pub async fn handle_anime_selected(
     }
 }
 
+// Pick the variant from the HLS manifest; the MP4 link is only a fallback
+async fn select_play_url(container: &Arc<Container>, quality: &StreamQuality) -> String {
+    match container.kodik().get_hls_variants(&quality.hls_url).await {
+        Ok(master) => master.select(VariantPreference::MaxHeight(quality.quality as u32))
+            .map(|variant| variant.uri.clone()),
+        Err(e) => {
+            warn!("Failed to load HLS manifest, falling back to MP4: {}", e);
+            None
+        }
+    }.unwrap_or_else(|| quality.mp4_url.clone())
+}
+
+// Download the MP4 behind the selected variant; the quality's own MP4 link is the fallback
+async fn select_download_url(container: &Arc<Container>, quality: &StreamQuality) -> String {
+    let variant_url = select_play_url(container, quality).await;
+    parsing::mp4_url_of(&variant_url).unwrap_or_else(|| quality.mp4_url.clone())
+}
+
+// Signed stream links expire within minutes, so the next episode is resolved
+// once when playback starts and again shortly before the current one ends
+const PREFETCH_REFRESH_BEFORE_END: f64 = 120.0;
+
+fn spawn_next_episode_prefetch(
+    container: Arc<Container>,
+    current_episode: Arc<Mutex<Option<CurrentEpisode>>>,
+    playing: CurrentEpisode
+) {
+    // Subscribe before the first prefetch so no progress report is missed
+    let mut mpv_events = container.mpv().subscribe_events();
+    tokio::spawn(async move {
+        let next = match container.kodik().get_anime_info(&playing.media_id).await {
+            Ok(info) => info.next_episode(playing.season, playing.episode),
+            Err(e) => {
+                warn!("Failed to load episode list for prefetch: {}", e);
+                return;
+            }
+        };
+        let Some((season, episode)) = next else {
+            debug!("No next episode to prefetch");
+            return;
+        };
+
+        let kodik = container.kodik();
+        let id = &playing.media_id;
+        let translation_id = playing.translation_id.as_str();
+
+        match kodik.prefetch_episode(id, season, episode, translation_id, playing.quality, false).await {
+            Ok(_) => debug!("Prefetched S{}E{}", season, episode),
+            Err(e) => warn!("Failed to prefetch S{}E{}: {}", season, episode, e),
+        }
+
+        loop {
+            let (position, duration) = match mpv_events.recv().await {
+                Ok(MpvEvent::Progress { position, duration }) => (position, duration),
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return,
+            };
+            if current_episode.lock().await.as_ref() != Some(&playing) {
+                debug!("Episode changed, stopping prefetch of S{}E{}", season, episode);
+                return;
+            }
+
+            if duration > 0.0 && duration - position <= PREFETCH_REFRESH_BEFORE_END {
+                match kodik.prefetch_episode(id, season, episode, translation_id, playing.quality, true).await {
+                    Ok(_) => debug!("Refreshed prefetched S{}E{}", season, episode),
+                    Err(e) => warn!("Failed to refresh prefetched S{}E{}: {}", season, episode, e),
+                }
+                return;
+            }
+        }
+    });
+}
+
 pub async fn handle_play_episode(
     container: &Arc<Container>,
     payload: Value,
@@ -120,12 +278,12 @@ pub async fn handle_play_episode(
     debug!("Handling play_episode request: {:?}", payload);
 
     // Extract required fields
-    let shikimori_id = extract_str_field(&payload, "shikimoriId");
+    let media_id = extract_media_id(&payload);
     let episode = extract_i64_field(&payload, "episode");
     let translation_id = extract_str_field(&payload, "translationId");
 
     // Match all results together
-    let (shikimori_id, episode, translation_id) = match (shikimori_id, episode, translation_id) {
+    let (media_id, episode, translation_id) = match (media_id, episode, translation_id) {
         (Ok(id), Ok(ep), Ok(tr_id)) => (id, ep as i32, tr_id),
         _ => {
             error!("Invalid play episode request parameters");
@@ -135,28 +293,59 @@ pub async fn handle_play_episode(
         }
     };
 
+    // Season is optional for backwards compatibility with single-season titles
+    let season = extract_i64_field(&payload, "season").unwrap_or(1) as i32;
+
+    let preferred_quality = payload.get("quality")
+        .and_then(|v| v.as_i64())
+        .map(|q| q as i32)
+        .or_else(|| container.storage().load().preferred_quality);
+
+    let playing = CurrentEpisode {
+        media_id: media_id.clone(),
+        season,
+        episode,
+        translation_id: translation_id.to_string(),
+        quality: preferred_quality
+    };
+
     // Update current episode with proper scope
     {
         let mut episode_guard = current_episode.lock().await;
-        *episode_guard = Some(CurrentEpisode {
-            shikimori_id: shikimori_id.to_string(),
-            episode,
-            translation_id: translation_id.to_string()
-        });
+        *episode_guard = Some(playing.clone());
     }
 
     match container.kodik().get_episode_link(
-        shikimori_id,
+        &media_id,
+        season,
         episode,
         translation_id
     ).await {
-        Ok((url, _)) => {
-            match container.mpv().play(&format!("https://{}/720.mp4/", url)) {
-                Ok(_) => Some(IpcResponse::Success { 
-                    data: serde_json::json!({
-                        "message": "Started playback"
+        Ok(stream) => {
+            let quality = match stream.select(preferred_quality) {
+                Some(quality) => quality,
+                None => {
+                    error!("No stream qualities available");
+                    return Some(IpcResponse::Error {
+                        message: "No stream qualities available".to_string()
+                    });
+                }
+            };
+            debug!("Selected {}p (preferred: {:?})", quality.quality, preferred_quality);
+
+            let play_url = select_play_url(container, quality).await;
+
+            match container.mpv().play(&play_url, None) {
+                Ok(_) => {
+                    spawn_next_episode_prefetch(container.clone(), current_episode, playing);
+                    Some(IpcResponse::Success {
+                        data: serde_json::json!({
+                            "message": "Started playback",
+                            "quality": quality.quality,
+                            "qualities": stream.qualities.iter().map(|q| q.quality).collect::<Vec<_>>()
+                        })
                     })
-                }),
+                },
                 Err(e) => {
                     error!("Failed to start playback: {}", e);
                     Some(IpcResponse::Error {
@@ -174,6 +363,47 @@ pub async fn handle_play_episode(
     }
 }
 
+// Called when mpv reports that the stream broke mid-playback: signed links
+// expire, so the episode is resolved again and restarted at the same position
+pub async fn handle_stream_failure(
+    container: &Arc<Container>,
+    current_episode: Arc<Mutex<Option<CurrentEpisode>>>,
+    position: f64
+) {
+    let Some(current) = current_episode.lock().await.clone() else {
+        debug!("Stream failed with no current episode, nothing to refresh");
+        return;
+    };
+
+    info!(
+        "Refreshing stream link for {} S{}E{} at {:.1}s",
+        current.media_id, current.season, current.episode, position
+    );
+
+    let stream = match container.kodik().refresh_episode_link(
+        &current.media_id,
+        current.season,
+        current.episode,
+        &current.translation_id
+    ).await {
+        Ok(stream) => stream,
+        Err(e) => {
+            error!("Failed to refresh episode link: {}", e);
+            return;
+        }
+    };
+
+    let Some(quality) = stream.select(current.quality) else {
+        error!("Refreshed stream has no qualities");
+        return;
+    };
+
+    let play_url = select_play_url(container, quality).await;
+    if let Err(e) = container.mpv().play(&play_url, Some(position)) {
+        error!("Failed to restart playback: {}", e);
+    }
+}
+
 pub async fn handle_get_playback_info(
     container: &Arc<Container>,
 ) -> Option<IpcResponse> {
@@ -229,12 +459,517 @@ pub async fn handle_stop_playback(
 
 
 pub async fn handle_start_download(
-    _container: &Arc<Container>,
-    _payload: Value
+    container: &Arc<Container>,
+    payload: Value
 ) -> Option<IpcResponse> {
-    // TODO: Implement download handling
+    debug!("Handling start_download request: {:?}", payload);
+
+    let media_id = extract_media_id(&payload);
+    let episode = extract_i64_field(&payload, "episode");
+    let translation_id = extract_str_field(&payload, "translationId");
+
+    let (media_id, episode, translation_id) = match (media_id, episode, translation_id) {
+        (Ok(id), Ok(ep), Ok(tr_id)) => (id, ep as i32, tr_id),
+        _ => {
+            error!("Invalid download request parameters");
+            return Some(IpcResponse::Error {
+                message: "Invalid download parameters".to_string()
+            });
+        }
+    };
+
+    let season = extract_i64_field(&payload, "season").unwrap_or(1) as i32;
+
+    let preferred_quality = payload.get("quality")
+        .and_then(|v| v.as_i64())
+        .map(|q| q as i32)
+        .or_else(|| container.storage().load().preferred_quality);
+
+    let stream = match container.kodik().get_episode_link(&media_id, season, episode, translation_id).await {
+        Ok(stream) => stream,
+        Err(e) => {
+            error!("Failed to get download link: {}", e);
+            return Some(IpcResponse::Error {
+                message: format!("Failed to get download link: {}", e)
+            });
+        }
+    };
+
+    let quality = match stream.select(preferred_quality) {
+        Some(quality) => quality,
+        None => return Some(IpcResponse::Error {
+            message: "No stream qualities available".to_string()
+        })
+    };
+
+    // Hand the direct MP4 link over to the browser's download manager
+    let url = select_download_url(container, quality).await;
+    if let Err(e) = open::that(&url) {
+        error!("Failed to open download URL: {}", e);
+        return Some(IpcResponse::Error {
+            message: format!("Failed to start download: {}", e)
+        });
+    }
+
     Some(IpcResponse::Success { 
-        data: serde_json::json!({ "status": "started" })
+        data: serde_json::json!({
+            "status": "started",
+            "quality": quality.quality,
+            "url": url
+        })
+    })
+}
+
+pub async fn handle_create_playlist(
+    container: &Arc<Container>,
+    payload: Value,
+    emitter: EventEmitter,
+    jobs: PlaylistJobs
+) -> Option<IpcResponse> {
+    debug!("Handling create_playlist request: {:?}", payload);
+
+    let media_id = extract_media_id(&payload);
+    let translation_id = extract_str_field(&payload, "translationId");
+
+    let (media_id, translation_id) = match (media_id, translation_id) {
+        (Ok(id), Ok(tr_id)) => (id, tr_id.to_string()),
+        _ => {
+            error!("Invalid playlist request parameters");
+            return Some(IpcResponse::Error {
+                message: "Invalid playlist parameters".to_string()
+            });
+        }
+    };
+
+    let title = extract_str_field(&payload, "title").unwrap_or("Playlist").to_string();
+    let preferred_quality = payload.get("quality")
+        .and_then(|v| v.as_i64())
+        .map(|q| q as i32)
+        .or_else(|| container.storage().load().preferred_quality);
+
+    // Generation can take longer than the IPC timeout, so the request only
+    // starts a job; progress and the result arrive as events
+    let job_id = uuid::Uuid::new_v4().to_string();
+    let cancel = CancellationToken::new();
+    jobs.lock().await.insert(job_id.clone(), cancel.clone());
+
+    let container = container.clone();
+    let task_job_id = job_id.clone();
+    tokio::spawn(async move {
+        let (progress_tx, mut progress_rx) = tokio::sync::mpsc::unbounded_channel();
+
+        let progress_emitter = emitter.clone();
+        let progress_job_id = task_job_id.clone();
+        let forward = tokio::spawn(async move {
+            while let Some(progress) = progress_rx.recv().await {
+                progress_emitter.emit(IpcEvent::PlaylistProgress {
+                    job_id: progress_job_id.clone(),
+                    resolved: progress.resolved,
+                    total: progress.total,
+                    entry: progress.entry
+                }).await;
+            }
+        });
+
+        let result = container.kodik().create_playlist(
+            &title,
+            &media_id,
+            &translation_id,
+            preferred_quality,
+            Some(progress_tx),
+            cancel
+        ).await;
+
+        // All progress events go out before the final one
+        let _ = forward.await;
+        jobs.lock().await.remove(&task_job_id);
+
+        let event = match result {
+            Ok(playlist) => {
+                let failed = playlist.failed().count();
+                if failed > 0 {
+                    error!("Playlist {}: {} episode(s) failed to resolve", task_job_id, failed);
+                }
+                IpcEvent::PlaylistReady {
+                    job_id: task_job_id,
+                    m3u8: Some(playlist.to_m3u8()),
+                    playlist: Some(playlist),
+                    error: None
+                }
+            },
+            Err(e) => {
+                error!("Playlist {} failed: {}", task_job_id, e);
+                IpcEvent::PlaylistReady {
+                    job_id: task_job_id,
+                    playlist: None,
+                    m3u8: None,
+                    error: Some(e.to_string())
+                }
+            }
+        };
+        emitter.emit(event).await;
+    });
+
+    Some(IpcResponse::Success {
+        data: serde_json::json!({ "jobId": job_id })
+    })
+}
+
+pub async fn handle_cancel_playlist(
+    payload: Value,
+    jobs: PlaylistJobs
+) -> Option<IpcResponse> {
+    let job_id = match extract_str_field(&payload, "jobId") {
+        Ok(job_id) => job_id,
+        Err(e) => return Some(IpcResponse::Error {
+            message: e.to_string()
+        })
+    };
+
+    match jobs.lock().await.remove(job_id) {
+        Some(cancel) => {
+            cancel.cancel();
+            Some(IpcResponse::Success {
+                data: serde_json::json!({ "status": "cancelled" })
+            })
+        },
+        None => Some(IpcResponse::Error {
+            message: format!("Unknown playlist job: {}", job_id)
+        })
+    }
+}
+
+pub async fn handle_export_playlist(
+    payload: Value
+) -> Option<IpcResponse> {
+    debug!("Handling export_playlist request");
+
+    let playlist = match payload.get("playlist").cloned().map(serde_json::from_value::<Playlist>) {
+        Some(Ok(playlist)) => playlist,
+        Some(Err(e)) => return Some(IpcResponse::Error {
+            message: format!("Invalid playlist field: {}", e)
+        }),
+        None => return Some(IpcResponse::Error {
+            message: "Missing playlist field".to_string()
+        })
+    };
+
+    let path = match extract_str_field(&payload, "path") {
+        Ok(path) => std::path::PathBuf::from(path),
+        Err(e) => return Some(IpcResponse::Error {
+            message: e.to_string()
+        })
+    };
+
+    // An explicit format wins, otherwise it follows the file extension
+    let format = match payload.get("format").filter(|v| !v.is_null()) {
+        Some(value) => match serde_json::from_value::<PlaylistFormat>(value.clone()) {
+            Ok(format) => Some(format),
+            Err(_) => return Some(IpcResponse::Error {
+                message: format!("Unknown playlist format: {}", value)
+            }),
+        },
+        None => PlaylistFormat::from_path(&path),
+    };
+
+    let Some(format) = format else {
+        return Some(IpcResponse::Error {
+            message: format!("Unknown playlist format for {}", path.display())
+        });
+    };
+
+    match playlist.write_to(&path, format) {
+        Ok(_) => Some(IpcResponse::Success {
+            data: serde_json::json!({
+                "path": path.display().to_string(),
+                "format": format,
+                "entries": playlist.entries.len() - playlist.failed().count()
+            })
+        }),
+        Err(e) => {
+            error!("Failed to export playlist: {}", e);
+            Some(IpcResponse::Error {
+                message: e.to_string()
+            })
+        }
+    }
+}
+
+pub async fn handle_follow_title(
+    container: &Arc<Container>,
+    payload: Value
+) -> Option<IpcResponse> {
+    debug!("Handling follow_title request: {:?}", payload);
+
+    let media_id = match extract_media_id(&payload) {
+        Ok(id) => id,
+        Err(e) => return Some(IpcResponse::Error {
+            message: e.to_string()
+        })
+    };
+    let title = extract_str_field(&payload, "title").unwrap_or_default().to_string();
+
+    // Current episode counts are the baseline; without them the first
+    // background check records the baseline instead
+    let translations = container.kodik().get_available_translations(&media_id).await
+        .unwrap_or_else(|e| {
+            warn!("Failed to load translations for {}: {}", media_id, e);
+            Vec::new()
+        });
+
+    match container.follows().follow(media_id, title, &translations) {
+        Ok(followed) => Some(IpcResponse::Success {
+            data: serde_json::json!({ "followed": followed })
+        }),
+        Err(e) => {
+            error!("Failed to follow title: {}", e);
+            Some(IpcResponse::Error {
+                message: format!("Failed to follow title: {}", e)
+            })
+        }
+    }
+}
+
+pub async fn handle_unfollow_title(
+    container: &Arc<Container>,
+    payload: Value
+) -> Option<IpcResponse> {
+    let media_id = match extract_media_id(&payload) {
+        Ok(id) => id,
+        Err(e) => return Some(IpcResponse::Error {
+            message: e.to_string()
+        })
+    };
+
+    match container.follows().unfollow(&media_id) {
+        Ok(removed) => Some(IpcResponse::Success {
+            data: serde_json::json!({ "removed": removed })
+        }),
+        Err(e) => {
+            error!("Failed to unfollow title: {}", e);
+            Some(IpcResponse::Error {
+                message: format!("Failed to unfollow title: {}", e)
+            })
+        }
+    }
+}
+
+pub async fn handle_get_follows(
+    container: &Arc<Container>
+) -> Option<IpcResponse> {
+    let follows = match container.follows().load() {
+        Ok(follows) => follows,
+        Err(e) => {
+            error!("Failed to load follows: {}", e);
+            return Some(IpcResponse::Error {
+                message: format!("Failed to load follows: {}", e)
+            });
+        }
+    };
+    let unseen = follows.updates.iter().filter(|u| !u.seen).count();
+
+    Some(IpcResponse::Success {
+        data: serde_json::json!({
+            "titles": follows.titles,
+            "updates": follows.updates,
+            "unseen": unseen
+        })
+    })
+}
+
+pub async fn handle_check_follows(
+    container: &Arc<Container>,
+    emitter: EventEmitter
+) -> Option<IpcResponse> {
+    debug!("Handling check_follows request");
+
+    let updates = check_followed_titles(container).await;
+    if !updates.is_empty() {
+        emitter.emit(IpcEvent::FollowUpdates { updates: updates.clone() }).await;
+    }
+
+    Some(IpcResponse::Success {
+        data: serde_json::json!({ "updates": updates })
+    })
+}
+
+pub async fn handle_mark_follow_updates_seen(
+    container: &Arc<Container>
+) -> Option<IpcResponse> {
+    match container.follows().mark_seen() {
+        Ok(_) => Some(IpcResponse::Success {
+            data: serde_json::json!({ "status": "ok" })
+        }),
+        Err(e) => Some(IpcResponse::Error {
+            message: e.to_string()
+        })
+    }
+}
+
+pub async fn handle_get_kodik_status(
+    container: &Arc<Container>
+) -> Option<IpcResponse> {
+    debug!("Handling get_kodik_status request");
+
+    match container.kodik().token_status().await {
+        Some(status) => Some(IpcResponse::Success {
+            data: serde_json::json!({
+                "token": status.token,
+                "source": status.source,
+                "candidates": status.candidates
+            })
+        }),
+        None => Some(IpcResponse::Error {
+            message: "No Kodik token available".to_string()
+        })
+    }
+}
+
+pub async fn handle_get_anime_details(
+    container: &Arc<Container>,
+    payload: Value
+) -> Option<IpcResponse> {
+    debug!("Handling get_anime_details request: {:?}", payload);
+
+    let shikimori_id = match extract_media_id(&payload) {
+        Ok(MediaId::Shikimori(id)) => id.parse::<i64>().ok(),
+        _ => None
+    };
+    let Some(shikimori_id) = shikimori_id else {
+        return Some(IpcResponse::Error {
+            message: "Anime details need a Shikimori id".to_string()
+        });
+    };
+
+    match container.shikimori().get_anime(shikimori_id).await {
+        Ok(anime) => Some(IpcResponse::Success {
+            data: serde_json::json!({
+                "plainDescription": anime.plain_description(),
+                "anime": anime
+            })
+        }),
+        Err(e) => {
+            error!("Failed to get anime details: {}", e);
+            Some(IpcResponse::Error {
+                message: format!("Failed to get anime details: {}", e)
+            })
+        }
+    }
+}
+
+pub async fn handle_get_user_rates(
+    container: &Arc<Container>
+) -> Option<IpcResponse> {
+    let rates = match container.shikimori().get_user_rates() {
+        Ok(rates) => rates,
+        Err(e) => {
+            error!("Failed to load user rates: {}", e);
+            return Some(IpcResponse::Error {
+                message: format!("Failed to load user rates: {}", e)
+            });
+        }
+    };
+
+    Some(IpcResponse::Success {
+        data: serde_json::json!({
+            "rates": rates.rates,
+            "pending": rates.pending.len(),
+            "syncedAt": rates.synced_at
+        })
+    })
+}
+
+pub async fn handle_sync_user_rates(
+    container: &Arc<Container>
+) -> Option<IpcResponse> {
+    debug!("Handling sync_user_rates request");
+
+    match container.shikimori().sync_user_rates().await {
+        Ok(report) => Some(IpcResponse::Success {
+            data: serde_json::json!({ "report": report })
+        }),
+        Err(e) => {
+            error!("Failed to sync user rates: {}", e);
+            Some(IpcResponse::Error {
+                message: format!("Failed to sync user rates: {}", e)
+            })
+        }
+    }
+}
+
+pub async fn handle_update_user_rate(
+    container: &Arc<Container>,
+    payload: Value
+) -> Option<IpcResponse> {
+    debug!("Handling update_user_rate request: {:?}", payload);
+
+    let target_id = match extract_media_id(&payload) {
+        Ok(MediaId::Shikimori(id)) => id.parse::<i64>().ok(),
+        _ => None
+    };
+    let Some(target_id) = target_id else {
+        return Some(IpcResponse::Error {
+            message: "User rates need a Shikimori id".to_string()
+        });
+    };
+
+    let status = match payload.get("status").filter(|v| !v.is_null()).cloned().map(serde_json::from_value::<RateStatus>) {
+        Some(Ok(status)) => Some(status),
+        Some(Err(e)) => return Some(IpcResponse::Error {
+            message: format!("Invalid status field: {}", e)
+        }),
+        None => None
+    };
+    let episodes = payload.get("episodes").and_then(|v| v.as_i64()).map(|e| e as i32);
+    let score = payload.get("score").and_then(|v| v.as_i64()).map(|s| s as i32);
+
+    let change = RateChange::new(target_id, status, episodes, score);
+    match container.shikimori().update_user_rate(change).await {
+        Ok(rate) => Some(IpcResponse::Success {
+            data: serde_json::json!({ "rate": rate })
+        }),
+        Err(e) => {
+            error!("Failed to update user rate: {}", e);
+            Some(IpcResponse::Error {
+                message: format!("Failed to update user rate: {}", e)
+            })
+        }
+    }
+}
+
+pub async fn handle_update_settings(
+    container: &Arc<Container>,
+    payload: Value
+) -> Option<IpcResponse> {
+    debug!("Handling update_settings request: {:?}", payload);
+
+    let mut settings = container.storage().load();
+
+    if let Some(quality) = payload.get("preferredQuality") {
+        settings.preferred_quality = quality.as_i64().map(|q| q as i32);
+    }
+
+    if let Some(auto_scrobble) = payload.get("autoScrobble").and_then(|v| v.as_bool()) {
+        settings.auto_scrobble = auto_scrobble;
+    }
+
+    if let Some(threshold) = payload.get("scrobbleThreshold").and_then(|v| v.as_f64()) {
+        settings.scrobble_threshold = threshold.clamp(0.1, 1.0);
+    }
+
+    if let Err(e) = container.storage().save(&settings) {
+        error!("Failed to save settings: {}", e);
+        return Some(IpcResponse::Error {
+            message: format!("Failed to save settings: {}", e)
+        });
+    }
+
+    Some(IpcResponse::Success {
+        data: serde_json::json!({
+            "preferredQuality": settings.preferred_quality,
+            "autoScrobble": settings.auto_scrobble,
+            "scrobbleThreshold": settings.scrobble_threshold
+        })
     })
 }
 
@@ -333,4 +1068,53 @@ pub async fn handle_open_auth_url(
             })
         }
     }
+}
+
+/// Start login through the loopback redirect; the result arrives as `authCompleted`.
+/// Without a configured port this fails and the UI falls back to pasting the code.
+pub async fn handle_start_loopback_login(
+    container: &Arc<Container>,
+    emitter: EventEmitter
+) -> Option<IpcResponse> {
+    debug!("Handling start_loopback_login request");
+
+    let Some(port) = CONFIG.shikimori_redirect_port else {
+        return Some(IpcResponse::Error {
+            message: "Loopback login is not configured".to_string()
+        });
+    };
+
+    let container = container.clone();
+    tokio::spawn(async move {
+        let result = match container.shikimori().login_loopback(port).await {
+            Ok(_token) => container.shikimori().get_user_info().await,
+            Err(e) => Err(e),
+        };
+
+        let event = match result {
+            Ok(user) => {
+                info!("Logged in to Shikimori as {}", user.nickname);
+                IpcEvent::AuthCompleted {
+                    user: Some(serde_json::json!({
+                        "username": user.nickname,
+                        "avatar": user.avatar,
+                        "id": user.id
+                    })),
+                    error: None
+                }
+            },
+            Err(e) => {
+                error!("Loopback login failed: {}", e);
+                IpcEvent::AuthCompleted {
+                    user: None,
+                    error: Some(e.to_string())
+                }
+            }
+        };
+        emitter.emit(event).await;
+    });
+
+    Some(IpcResponse::Success {
+        data: serde_json::json!({ "started": true })
+    })
 }
\ No newline at end of file
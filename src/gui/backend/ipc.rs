@@ -1,6 +1,8 @@
 use serde::Serialize;
-use crate::kodik::MediaResult;
+use crate::kodik::{AvailableTranslation, MediaId, MediaResult, Playlist, PlaylistEntry, Season, TranslationKind};
 use serde_json::Value;
+use crate::storage::FollowUpdate;
+use crate::shikimori::{SyncReport, UserRate};
 
 #[derive(Serialize, Debug)]
 #[serde(tag = "type")]
@@ -17,13 +19,20 @@ pub enum IpcResponse {
 
     #[serde(rename = "searchResults")]
     SearchResults {
-        results: Vec<MediaResult>
+        results: Vec<SearchResultItem>
+    },
+
+    #[serde(rename = "browseResults")]
+    BrowseResults {
+        results: Vec<SearchResultItem>,
+        next_cursor: Option<String>
     },
 
     #[serde(rename = "animeInfo")]
     AnimeInfo {
         translations: Vec<TranslationInfo>,
-        episodes: i32
+        episodes: i32,
+        seasons: Vec<Season>
     },
 
     #[serde(rename = "authUrl")]
@@ -43,9 +52,115 @@ pub enum IpcResponse {
     }
 }
 
+/// Events pushed to the UI without a matching request
+#[derive(Serialize, Debug)]
+#[serde(tag = "type")]
+pub enum IpcEvent {
+    #[serde(rename = "playlistProgress")]
+    PlaylistProgress {
+        job_id: String,
+        resolved: usize,
+        total: usize,
+        entry: PlaylistEntry
+    },
+
+    #[serde(rename = "playlistReady")]
+    PlaylistReady {
+        job_id: String,
+        playlist: Option<Playlist>,
+        m3u8: Option<String>,
+        error: Option<String>
+    },
+
+    #[serde(rename = "followUpdates")]
+    FollowUpdates {
+        updates: Vec<FollowUpdate>
+    },
+
+    #[serde(rename = "userRatesSynced")]
+    UserRatesSynced {
+        report: SyncReport
+    },
+
+    #[serde(rename = "episodeScrobbled")]
+    EpisodeScrobbled {
+        media_id: MediaId,
+        rate: UserRate
+    },
+
+    #[serde(rename = "authCompleted")]
+    AuthCompleted {
+        user: Option<Value>,
+        error: Option<String>
+    },
+
+    #[serde(rename = "reloginRequired")]
+    ReloginRequired {
+        reason: String
+    }
+}
+
 #[derive(Serialize, Debug)]
 pub struct TranslationInfo {
     pub id: String,
     pub title: String,
-    pub episodes: i32
+    pub episodes: i32,
+    pub kind: TranslationKind
+}
+
+/// Normalized search/browse entry sent to the UI
+#[derive(Serialize, Debug)]
+pub struct SearchResultItem {
+    pub media_id: Option<MediaId>,
+    pub shikimori_id: Option<String>,
+    pub kinopoisk_id: Option<String>,
+    pub imdb_id: Option<String>,
+    pub title: String,
+    pub title_orig: String,
+    pub kind: Option<String>,
+    pub year: i32,
+    pub poster: Option<String>,
+    pub description: Option<String>,
+    pub genres: Vec<String>,
+    pub score: Option<f32>,
+    pub status: Option<String>,
+    pub episodes_aired: Option<i32>,
+    pub episodes_total: Option<i32>,
+    pub screenshots: Vec<String>,
+    pub translations: Vec<AvailableTranslation>,
+    pub updated_at: Option<String>,
+}
+
+impl From<MediaResult> for SearchResultItem {
+    fn from(result: MediaResult) -> Self {
+        let material = result.material_data.unwrap_or_default();
+
+        Self {
+            media_id: result.media_id,
+            shikimori_id: result.shikimori_id,
+            kinopoisk_id: result.kinopoisk_id,
+            imdb_id: result.imdb_id,
+            // Prefer the localized anime title over the generic one
+            title: material.anime_title.clone()
+                .or_else(|| material.title.clone())
+                .unwrap_or(result.title),
+            title_orig: result.title_orig,
+            kind: material.anime_kind.clone().or(result.media_type),
+            year: material.year.unwrap_or(result.year),
+            poster: material.poster().map(str::to_string),
+            description: material.description().map(str::to_string),
+            genres: material.genres().to_vec(),
+            score: material.score(),
+            status: material.status().map(str::to_string),
+            episodes_aired: material.episodes_aired.or(result.last_episode),
+            episodes_total: material.episodes_total,
+            screenshots: if material.screenshots.is_empty() {
+                result.screenshots
+            } else {
+                material.screenshots
+            },
+            translations: result.translations,
+            updated_at: result.updated_at,
+        }
+    }
 }
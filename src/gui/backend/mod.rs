@@ -5,19 +5,30 @@ mod server;
 mod handlers;
 mod types;
 mod scripts;
+mod events;
+mod follows;
+mod user_rates;
+mod scrobble;
+mod auth;
 
 pub use webview::*;
 pub use ipc::*;
 pub use event_loop::*;
 pub use handlers::*;
 pub use types::*;
+pub use events::*;
+pub use follows::*;
+pub use user_rates::*;
+pub use scrobble::*;
 pub use crate::utils::routes::*;
 
 // Re-export specific handlers
 pub use handlers::{
     handle_search,
+    handle_browse,
     handle_anime_selected,
     handle_play_episode,
+    handle_stream_failure,
     handle_get_playback_info,
     handle_toggle_playback,
     handle_stop_playback,
@@ -26,4 +37,19 @@ pub use handlers::{
     handle_get_user_info,
     handle_logout,
     handle_open_auth_url,
+    handle_start_loopback_login,
+    handle_update_settings,
+    handle_get_kodik_status,
+    handle_get_anime_details,
+    handle_create_playlist,
+    handle_cancel_playlist,
+    handle_export_playlist,
+    handle_follow_title,
+    handle_unfollow_title,
+    handle_get_follows,
+    handle_check_follows,
+    handle_mark_follow_updates_seen,
+    handle_get_user_rates,
+    handle_sync_user_rates,
+    handle_update_user_rate,
 };
\ No newline at end of file
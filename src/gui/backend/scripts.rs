@@ -7,6 +7,12 @@ pub fn get_full_init_script() -> String {
         window.dispatchEvent(event);
     };
 
+    window.__IPC_EVENT__ = function(event) {
+        window.dispatchEvent(new CustomEvent('ipc-event', {
+            detail: event
+        }));
+    };
+
     window._ipc_ = {
         send: function(type, payload) {
             const id = Math.random().toString(36).substr(2, 9);
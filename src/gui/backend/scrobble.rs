@@ -0,0 +1,128 @@
+use std::sync::Arc;
+use anyhow::Result;
+use tokio::sync::{mpsc, Mutex};
+use log::{debug, info, warn};
+use crate::di::Container;
+use crate::gui::backend::events::EventEmitter;
+use crate::gui::backend::ipc::IpcEvent;
+use crate::gui::backend::types::CurrentEpisode;
+use crate::kodik::MediaId;
+use crate::shikimori::{RateChange, RateStatus};
+
+/// Progress reports waiting for the scrobbler; new ones are dropped while it is busy
+const PROGRESS_QUEUE: usize = 4;
+
+/// Marks episodes as watched on Shikimori once playback crosses the configured threshold
+pub struct Scrobbler {
+    container: Arc<Container>,
+    emitter: EventEmitter,
+    /// Last episode scrobbled or skipped for good, so each playback is counted once;
+    /// failed attempts leave it unset and are retried on the next report
+    last: Option<CurrentEpisode>,
+}
+
+impl Scrobbler {
+    pub fn new(container: Arc<Container>, emitter: EventEmitter) -> Self {
+        Self {
+            container,
+            emitter,
+            last: None,
+        }
+    }
+
+    /// Run on a separate task, so Shikimori calls never hold up the mpv event loop;
+    /// progress reports (position, duration) go through the returned sender
+    pub fn spawn(mut self, current_episode: Arc<Mutex<Option<CurrentEpisode>>>) -> mpsc::Sender<(f64, f64)> {
+        let (tx, mut rx) = mpsc::channel(PROGRESS_QUEUE);
+        tokio::spawn(async move {
+            while let Some((position, duration)) = rx.recv().await {
+                self.on_progress(&current_episode, position, duration).await;
+            }
+        });
+        tx
+    }
+
+    pub async fn on_progress(
+        &mut self,
+        current_episode: &Arc<Mutex<Option<CurrentEpisode>>>,
+        position: f64,
+        duration: f64
+    ) {
+        let settings = self.container.storage().load();
+        if !settings.auto_scrobble || duration <= 0.0 || position / duration < settings.scrobble_threshold {
+            return;
+        }
+
+        let Some(current) = current_episode.lock().await.clone() else {
+            return;
+        };
+        if self.last.as_ref() == Some(&current) {
+            return;
+        }
+
+        let target_id = match &current.media_id {
+            MediaId::Shikimori(id) => id.parse::<i64>().ok(),
+            _ => None
+        };
+        let Some(target_id) = target_id else {
+            debug!("Not scrobbling {}: no Shikimori id", current.media_id);
+            self.last = Some(current);
+            return;
+        };
+
+        if !self.container.shikimori().is_logged_in().await {
+            debug!("Not scrobbling {}: not logged in to Shikimori", current.media_id);
+            return;
+        }
+
+        let change = match self.rate_change(target_id, current.episode).await {
+            Ok(Some(change)) => change,
+            Ok(None) => {
+                self.last = Some(current);
+                return;
+            },
+            Err(e) => {
+                warn!("Not scrobbling {}: {}", target_id, e);
+                return;
+            }
+        };
+
+        match self.container.shikimori().update_user_rate(change).await {
+            Ok(rate) => {
+                info!("Scrobbled episode {} of {} ({:?})", rate.episodes, target_id, rate.status);
+                self.last = Some(current.clone());
+                self.emitter.emit(IpcEvent::EpisodeScrobbled {
+                    media_id: current.media_id,
+                    rate
+                }).await;
+            },
+            Err(e) => warn!("Failed to scrobble episode {} of {}: {}", current.episode, target_id, e),
+        }
+    }
+
+    /// Raise the episode counter; the last episode completes the title.
+    /// `None` when the episode is already counted
+    async fn rate_change(&self, target_id: i64, episode: i32) -> Result<Option<RateChange>> {
+        let rate = self.container.shikimori().get_user_rates()?.rate(target_id).cloned();
+        if rate.as_ref().is_some_and(|rate| rate.episodes >= episode) {
+            debug!("Episode {} of {} is already counted", episode, target_id);
+            return Ok(None);
+        }
+
+        // Ongoing titles report 0 episodes until the total is known
+        let total = match self.container.shikimori().get_anime(target_id).await {
+            Ok(anime) => anime.episodes.filter(|total| *total > 0),
+            Err(e) => {
+                warn!("Failed to load episode count of {}: {}", target_id, e);
+                None
+            }
+        };
+
+        let status = match (total, rate.map(|rate| rate.status)) {
+            (Some(total), _) if episode >= total => RateStatus::Completed,
+            (_, Some(RateStatus::Rewatching)) => RateStatus::Rewatching,
+            _ => RateStatus::Watching,
+        };
+        Ok(Some(RateChange::new(target_id, Some(status), Some(episode), None)))
+    }
+}
@@ -1,8 +1,18 @@
+use std::collections::HashMap;
+use std::sync::Arc;
 use serde::{Serialize, Deserialize};
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+use crate::kodik::MediaId;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CurrentEpisode {
-    pub shikimori_id: String,
+    pub media_id: MediaId,
+    pub season: i32,
     pub episode: i32,
-    pub translation_id: String
+    pub translation_id: String,
+    pub quality: Option<i32>
 }
+
+/// Running playlist jobs by job id, used to cancel them from the UI
+pub type PlaylistJobs = Arc<Mutex<HashMap<String, CancellationToken>>>;
@@ -0,0 +1,28 @@
+use std::sync::Arc;
+use std::time::Duration;
+use log::debug;
+use crate::di::Container;
+use crate::gui::backend::events::EventEmitter;
+use crate::gui::backend::ipc::IpcEvent;
+
+/// How often the Shikimori list is synchronized; queued offline edits go out with it
+const USER_RATES_SYNC_INTERVAL: Duration = Duration::from_secs(15 * 60);
+/// Delay before the first sync, so it doesn't compete with app startup
+const USER_RATES_FIRST_SYNC_DELAY: Duration = Duration::from_secs(10);
+
+/// Periodically synchronize user rates while logged in
+pub fn spawn_user_rates_sync(container: Arc<Container>, emitter: EventEmitter) {
+    tokio::spawn(async move {
+        tokio::time::sleep(USER_RATES_FIRST_SYNC_DELAY).await;
+        let mut interval = tokio::time::interval(USER_RATES_SYNC_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            match container.shikimori().sync_user_rates().await {
+                Ok(report) => emitter.emit(IpcEvent::UserRatesSynced { report }).await,
+                // Not logged in, or Shikimori rejected the request
+                Err(e) => debug!("Skipping user rates sync: {}", e),
+            }
+        }
+    });
+}
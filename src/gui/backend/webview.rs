@@ -7,8 +7,14 @@ use tokio::sync::mpsc::Sender;
 use crate::di::Container;
 use log::{info, error};
 use tokio::sync::mpsc;
-use crate::gui::backend::types::CurrentEpisode;
-use tokio::sync::Mutex;
+use crate::gui::backend::types::{CurrentEpisode, PlaylistJobs};
+use crate::gui::backend::events::EventEmitter;
+use crate::gui::backend::follows;
+use crate::gui::backend::user_rates;
+use crate::gui::backend::auth;
+use crate::gui::backend::scrobble::Scrobbler;
+use tokio::sync::{broadcast, Mutex};
+use crate::di::interfaces::MpvEvent;
 use crate::gui::backend::ipc::{self, IpcResponse};
 use crate::gui::backend::handlers;
 use crate::utils::routes::*;  // Import routes from parent module
@@ -27,6 +33,38 @@ pub fn create_webview(
     // Create current episode state with proper type
     let current_episode = Arc::new(Mutex::new(None::<CurrentEpisode>));
 
+    // Restart playback with a fresh link when mpv reports a broken stream,
+    // and mark watched episodes on Shikimori
+    {
+        let container = container.clone();
+        let current_episode = current_episode.clone();
+        let mut mpv_events = container.mpv().subscribe_events();
+        let scrobbler = Scrobbler::new(container.clone(), EventEmitter::new(script_tx.clone()))
+            .spawn(current_episode.clone());
+        tokio::spawn(async move {
+            loop {
+                match mpv_events.recv().await {
+                    Ok(MpvEvent::StreamFailed { position, .. }) => {
+                        handlers::handle_stream_failure(&container, current_episode.clone(), position).await;
+                    },
+                    Ok(MpvEvent::Progress { position, duration }) => {
+                        // A busy scrobbler skips this report, a later one catches up
+                        let _ = scrobbler.try_send((position, duration));
+                    },
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    follows::spawn_follows_checker(container.clone(), EventEmitter::new(script_tx.clone()));
+    user_rates::spawn_user_rates_sync(container.clone(), EventEmitter::new(script_tx.clone()));
+    auth::spawn_token_refresher(container.clone(), EventEmitter::new(script_tx.clone()));
+
+    // Playlist jobs outlive the request that started them
+    let playlist_jobs: PlaylistJobs = Arc::new(Mutex::new(std::collections::HashMap::new()));
+
     // In release mode, start local server
     #[cfg(not(debug_assertions))]
     let url = {
@@ -45,6 +83,7 @@ pub fn create_webview(
             let container = container.clone();
             let script_tx = script_tx.clone();
             let current_episode = current_episode.clone();
+            let playlist_jobs = playlist_jobs.clone();
             
             tokio::spawn(async move {
                 if let Err(e) = async {
@@ -60,7 +99,8 @@ pub fn create_webview(
                                 &msg,
                                 id.clone(),
                                 &script_tx,
-                                current_episode
+                                current_episode,
+                                playlist_jobs
                             ).await?;
                         },
                         Err(e) => {
@@ -103,6 +143,7 @@ async fn process_message(
     id: String,
     script_tx: &Sender<(String, String)>,
     current_episode: Arc<Mutex<Option<CurrentEpisode>>>,
+    playlist_jobs: PlaylistJobs,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let value: serde_json::Value = serde_json::from_str(msg)?;
     
@@ -118,6 +159,7 @@ async fn process_message(
 
     let response = match msg_type {
         API_SEARCH => handlers::handle_search(container, payload).await,
+        API_BROWSE => handlers::handle_browse(container, payload).await,
         API_ANIME_SELECTED => handlers::handle_anime_selected(container, payload).await,
         API_PLAY_EPISODE => handlers::handle_play_episode(container, payload, current_episode).await,
         API_GET_PLAYBACK_INFO => handlers::handle_get_playback_info(container).await,
@@ -128,6 +170,26 @@ async fn process_message(
         API_GET_USER_INFO => handlers::handle_get_user_info(container).await,
         API_LOGOUT => handlers::handle_logout(container).await,
         API_OPEN_AUTH_URL => handlers::handle_open_auth_url(container).await,
+        API_START_LOOPBACK_LOGIN => handlers::handle_start_loopback_login(container, EventEmitter::new(script_tx.clone())).await,
+        API_UPDATE_SETTINGS => handlers::handle_update_settings(container, payload).await,
+        API_GET_KODIK_STATUS => handlers::handle_get_kodik_status(container).await,
+        API_GET_ANIME_DETAILS => handlers::handle_get_anime_details(container, payload).await,
+        API_CREATE_PLAYLIST => handlers::handle_create_playlist(
+            container,
+            payload,
+            EventEmitter::new(script_tx.clone()),
+            playlist_jobs
+        ).await,
+        API_CANCEL_PLAYLIST => handlers::handle_cancel_playlist(payload, playlist_jobs).await,
+        API_EXPORT_PLAYLIST => handlers::handle_export_playlist(payload).await,
+        API_FOLLOW_TITLE => handlers::handle_follow_title(container, payload).await,
+        API_UNFOLLOW_TITLE => handlers::handle_unfollow_title(container, payload).await,
+        API_GET_FOLLOWS => handlers::handle_get_follows(container).await,
+        API_CHECK_FOLLOWS => handlers::handle_check_follows(container, EventEmitter::new(script_tx.clone())).await,
+        API_MARK_FOLLOW_UPDATES_SEEN => handlers::handle_mark_follow_updates_seen(container).await,
+        API_GET_USER_RATES => handlers::handle_get_user_rates(container).await,
+        API_SYNC_USER_RATES => handlers::handle_sync_user_rates(container).await,
+        API_UPDATE_USER_RATE => handlers::handle_update_user_rate(container, payload).await,
         _ => {
             let err = format!("Unknown message type: {}", msg_type);
             log::error!("{}", err);
@@ -142,6 +204,7 @@ async fn process_message(
                 IpcResponse::Success { .. } => "success",
                 IpcResponse::Error { .. } => "error", 
                 IpcResponse::SearchResults { .. } => "searchResults",
+                IpcResponse::BrowseResults { .. } => "browseResults",
                 IpcResponse::AnimeInfo { .. } => "animeInfo",
                 IpcResponse::AuthUrl { .. } => "authUrl",
                 IpcResponse::AuthStatus { .. } => "authStatus",
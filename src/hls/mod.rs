@@ -0,0 +1,10 @@
+//! Разбор HLS-плейлистов (master и media) и выбор варианта потока
+//!
+//! Разбор не делает сетевых запросов: текст плейлиста загружает вызывающий код,
+//! а относительные ссылки разрешаются относительно адреса самого плейлиста.
+
+mod playlist;
+
+pub use playlist::{
+    parse, HlsPlaylist, MasterPlaylist, MediaPlaylist, Resolution, Segment, Variant, VariantPreference,
+};
@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Разрешение варианта (`RESOLUTION=1280x720`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Resolution {
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Вариант потока из master-плейлиста
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Variant {
+    /// Абсолютная ссылка на media-плейлист варианта
+    pub uri: String,
+    /// Пиковый битрейт, бит/с (0, если не указан)
+    pub bandwidth: u64,
+    pub average_bandwidth: Option<u64>,
+    pub resolution: Option<Resolution>,
+    pub codecs: Option<String>,
+    pub frame_rate: Option<f64>,
+}
+
+/// Сегмент media-плейлиста
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Segment {
+    pub uri: String,
+    /// Длительность в секундах
+    pub duration: f64,
+    pub title: Option<String>,
+}
+
+/// Master-плейлист: список вариантов одного потока
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MasterPlaylist {
+    pub variants: Vec<Variant>,
+}
+
+/// Media-плейлист: сегменты одного варианта
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MediaPlaylist {
+    pub target_duration: Option<f64>,
+    pub media_sequence: u64,
+    pub segments: Vec<Segment>,
+    /// Есть `#EXT-X-ENDLIST`: плейлист полный, не трансляция
+    pub end_list: bool,
+}
+
+impl MediaPlaylist {
+    /// Общая длительность в секундах
+    pub fn duration(&self) -> f64 {
+        self.segments.iter().map(|s| s.duration).sum()
+    }
+}
+
+/// Результат разбора: по тексту заранее неизвестно, master это или media
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum HlsPlaylist {
+    Master(MasterPlaylist),
+    Media(MediaPlaylist),
+}
+
+/// Как выбирать вариант
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VariantPreference {
+    /// Наибольший битрейт
+    Highest,
+    /// Наибольшая высота кадра, не превышающая заданную
+    MaxHeight(u32),
+    /// Наибольший битрейт, не превышающий заданный (бит/с)
+    MaxBandwidth(u64),
+}
+
+impl MasterPlaylist {
+    /// Master-плейлист из одного варианта — для ссылки, которая сразу ведёт на media-плейлист
+    pub fn single(uri: &str) -> Self {
+        Self {
+            variants: vec![Variant {
+                uri: uri.to_string(),
+                bandwidth: 0,
+                average_bandwidth: None,
+                resolution: None,
+                codecs: None,
+                frame_rate: None,
+            }],
+        }
+    }
+
+    /// Выбрать вариант; если под ограничение не подходит ни один, берётся самый лёгкий
+    ///
+    /// Варианты без `RESOLUTION` под ограничение по высоте не подходят; если разрешения
+    /// нет ни у одного, выбирается наибольший битрейт.
+    pub fn select(&self, preference: VariantPreference) -> Option<&Variant> {
+        let lightest = || self.variants.iter().min_by_key(|v| (v.bandwidth, v.height()));
+        let highest = || self.variants.iter().max_by_key(|v| (v.bandwidth, v.height()));
+
+        match preference {
+            VariantPreference::Highest => highest(),
+            VariantPreference::MaxHeight(_) if self.variants.iter().all(|v| v.resolution.is_none()) => highest(),
+            VariantPreference::MaxHeight(height) => self.variants.iter()
+                .filter(|v| v.resolution.is_some() && v.height() <= height)
+                .max_by_key(|v| (v.height(), v.bandwidth))
+                .or_else(lightest),
+            VariantPreference::MaxBandwidth(bandwidth) => self.variants.iter()
+                .filter(|v| v.bandwidth <= bandwidth)
+                .max_by_key(|v| (v.bandwidth, v.height()))
+                .or_else(lightest),
+        }
+    }
+}
+
+impl Variant {
+    /// Высота кадра, 0 — если разрешение не указано
+    pub fn height(&self) -> u32 {
+        self.resolution.map(|r| r.height).unwrap_or(0)
+    }
+}
+
+/// Разобрать текст плейлиста; `base_url` — адрес, с которого он загружен
+pub fn parse(text: &str, base_url: &str) -> Result<HlsPlaylist> {
+    let base = Url::parse(base_url).map_err(|e| anyhow::anyhow!("Invalid playlist URL {}: {}", base_url, e))?;
+    let mut lines = text.lines()
+        .map(|line| line.trim().trim_start_matches('\u{feff}'))
+        .filter(|line| !line.is_empty());
+
+    if lines.next() != Some("#EXTM3U") {
+        return Err(anyhow::anyhow!("Not an HLS playlist: missing #EXTM3U"));
+    }
+
+    let mut variants = Vec::new();
+    let mut media = MediaPlaylist {
+        target_duration: None,
+        media_sequence: 0,
+        segments: Vec::new(),
+        end_list: false,
+    };
+    let mut pending_variant: Option<HashMap<String, String>> = None;
+    let mut pending_segment: Option<(f64, Option<String>)> = None;
+
+    for line in lines {
+        if let Some(attributes) = line.strip_prefix("#EXT-X-STREAM-INF:") {
+            pending_variant = Some(parse_attributes(attributes));
+        } else if let Some(info) = line.strip_prefix("#EXTINF:") {
+            let (duration, title) = info.split_once(',').unwrap_or((info, ""));
+            let duration = duration.trim().parse::<f64>()
+                .map_err(|_| anyhow::anyhow!("Invalid #EXTINF duration: {}", line))?;
+            let title = Some(title.trim()).filter(|t| !t.is_empty()).map(str::to_string);
+            pending_segment = Some((duration, title));
+        } else if let Some(value) = line.strip_prefix("#EXT-X-TARGETDURATION:") {
+            media.target_duration = value.trim().parse().ok();
+        } else if let Some(value) = line.strip_prefix("#EXT-X-MEDIA-SEQUENCE:") {
+            media.media_sequence = value.trim().parse().unwrap_or(0);
+        } else if line == "#EXT-X-ENDLIST" {
+            media.end_list = true;
+        } else if line.starts_with('#') {
+            // Остальные теги на выбор варианта не влияют
+        } else {
+            let uri = resolve(&base, line)?;
+            if let Some(attributes) = pending_variant.take() {
+                variants.push(variant_from(uri, &attributes));
+            } else if let Some((duration, title)) = pending_segment.take() {
+                media.segments.push(Segment { uri, duration, title });
+            }
+        }
+    }
+
+    if !variants.is_empty() {
+        Ok(HlsPlaylist::Master(MasterPlaylist { variants }))
+    } else if !media.segments.is_empty() {
+        Ok(HlsPlaylist::Media(media))
+    } else {
+        Err(anyhow::anyhow!("HLS playlist has neither variants nor segments"))
+    }
+}
+
+fn resolve(base: &Url, uri: &str) -> Result<String> {
+    base.join(uri)
+        .map(|url| url.to_string())
+        .map_err(|e| anyhow::anyhow!("Invalid URI {} in playlist: {}", uri, e))
+}
+
+fn variant_from(uri: String, attributes: &HashMap<String, String>) -> Variant {
+    let number = |key: &str| attributes.get(key).and_then(|v| v.parse::<u64>().ok());
+    let resolution = attributes.get("RESOLUTION").and_then(|value| {
+        let (width, height) = value.split_once('x')?;
+        Some(Resolution {
+            width: width.parse().ok()?,
+            height: height.parse().ok()?,
+        })
+    });
+
+    Variant {
+        uri,
+        bandwidth: number("BANDWIDTH").unwrap_or(0),
+        average_bandwidth: number("AVERAGE-BANDWIDTH"),
+        resolution,
+        codecs: attributes.get("CODECS").cloned(),
+        frame_rate: attributes.get("FRAME-RATE").and_then(|v| v.parse().ok()),
+    }
+}
+
+/// Разобрать список атрибутов `KEY=VALUE,KEY="VALUE, с запятыми"`
+fn parse_attributes(list: &str) -> HashMap<String, String> {
+    let mut attributes = HashMap::new();
+    let mut rest = list.trim();
+
+    while !rest.is_empty() {
+        let Some((key, after_key)) = rest.split_once('=') else {
+            break;
+        };
+
+        let (value, after_value) = match after_key.strip_prefix('"') {
+            Some(quoted) => match quoted.split_once('"') {
+                Some((value, after)) => (value, after),
+                None => (quoted, ""),
+            },
+            None => after_key.split_once(',').unwrap_or((after_key, "")),
+        };
+
+        attributes.insert(key.trim().to_ascii_uppercase(), value.trim().to_string());
+        rest = after_value.trim_start_matches(',').trim_start();
+    }
+
+    attributes
+}
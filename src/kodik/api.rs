@@ -1,15 +1,24 @@
 use anyhow::{Context, Result};
-use reqwest::Client;
-use scraper::{Html, Selector};
 use serde_json::json;
-use base64::Engine as _;
-use base64::engine::general_purpose::STANDARD as BASE64;
 use std::collections::HashMap;
 use std::io::{self, Write};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use regex::Regex;
 use tokio;
+use url::Url;
+use std::future::Future;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use crate::hls::{self, HlsPlaylist, MasterPlaylist};
+use super::cache::{CacheConfig, CacheKind, ResponseCache};
+use super::http::{HttpPolicy, KodikHttp};
+use super::material::{AvailableTranslation, MaterialData, ReleaseTranslation};
+use super::media_id::MediaId;
+use super::parsing::{self, PlayerKind, VideoInfo};
+use super::prefetch::PrefetchConfig;
+use super::query::SearchQuery;
 
 /// Структура ответа поиска
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -17,30 +26,161 @@ pub struct SearchResponse {
     pub total: i32,
     pub results: Vec<MediaResult>,
     pub error: Option<String>,
+    /// Ссылка на следующую страницу (`next_page` в ответе `list`)
+    #[serde(alias = "next_page")]
     pub next_page_id: Option<String>
 }
 
+/// Режим просмотра каталога
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum BrowseMode {
+    /// Онгоинги
+    Ongoing,
+    /// Вышедшие тайтлы
+    Released,
+    /// Недавно обновлённые (по дате обновления)
+    RecentlyUpdated,
+}
+
+impl BrowseMode {
+    /// Значение фильтра `anime_status` для режима
+    pub fn anime_status(&self) -> Option<&'static str> {
+        match self {
+            BrowseMode::Ongoing => Some("ongoing"),
+            BrowseMode::Released => Some("released"),
+            BrowseMode::RecentlyUpdated => None,
+        }
+    }
+}
+
+/// Сколько страниц `list` загружается ради одной страницы каталога
+const BROWSE_MAX_PAGES: usize = 4;
+
+/// Страница каталога с курсором продолжения
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrowsePage {
+    pub results: Vec<MediaResult>,
+    pub next_cursor: Option<String>,
+}
+
 /// Структура ответа с информацией
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct InfoResponse {
+    /// Число серий во всех сезонах
     pub series_count: i32,
-    pub translations: Vec<Translation>
+    pub translations: Vec<Translation>,
+    /// Сезоны сериала со своими списками серий (пусто для фильмов)
+    pub seasons: Vec<Season>,
+    /// Перевод, к которому относятся id и хэши серий в `seasons`
+    pub episodes_translation: Option<String>
+}
+
+impl InfoResponse {
+    /// Следующая серия после `episode` (сезон, серия): дальше по сезону или первая серия следующего сезона
+    pub fn next_episode(&self, season: i32, episode: i32) -> Option<(i32, i32)> {
+        self.seasons.iter()
+            .flat_map(|s| s.episodes.iter().map(move |e| (s.number, e.number)))
+            .filter(|next| *next > (season, episode))
+            .min()
+    }
+}
+
+/// Структура сезона
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Season {
+    pub number: i32,
+    pub episodes: Vec<EpisodeInfo>,
+}
+
+impl Season {
+    pub fn episode(&self, number: i32) -> Option<&EpisodeInfo> {
+        self.episodes.iter().find(|e| e.number == number)
+    }
+
+    /// Номера серий, пропущенных переводом (между первой и последней вышедшей)
+    pub fn missing_episodes(&self) -> Vec<i32> {
+        let (Some(first), Some(last)) = (
+            self.episodes.iter().map(|e| e.number).min(),
+            self.episodes.iter().map(|e| e.number).max(),
+        ) else {
+            return Vec::new();
+        };
+        (first..=last).filter(|n| self.episode(*n).is_none()).collect()
+    }
+}
+
+/// Серия со страницы плеера: номер, id и хэш материала серии
+///
+/// У части плееров `<option>` серии без `data-id`/`data-hash`, такие серии
+/// открываются через страницу плеера.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct EpisodeInfo {
+    pub number: i32,
+    pub media_id: Option<String>,
+    pub hash: Option<String>,
+}
+
+impl EpisodeInfo {
+    /// Прямая ссылка на страницу серии, без выбора перевода на странице плеера
+    pub fn page_url(&self) -> Option<String> {
+        Some(format!(
+            "https://kodik.info/seria/{}/{}/720p?min_age=16&first_url=false",
+            self.media_id.as_deref()?,
+            self.hash.as_deref()?
+        ))
+    }
 }
 
 /// Структура перевода
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 pub struct Translation {
     pub id: String,
-    pub translation_type: String,
+    pub translation_type: TranslationKind,
     pub name: String,
 }
 
+/// Тип перевода
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TranslationKind {
+    /// Озвучка
+    Voice,
+    /// Субтитры
+    Subtitles,
+    /// Kodik не указал тип или прислал незнакомый
+    #[serde(other)]
+    Unknown,
+}
+
+impl TranslationKind {
+    /// Тип по значению Kodik (`voice`, `subtitles`)
+    pub fn from_kodik(value: Option<&str>) -> Self {
+        match value {
+            Some("voice") => TranslationKind::Voice,
+            Some("subtitles") => TranslationKind::Subtitles,
+            _ => TranslationKind::Unknown,
+        }
+    }
+
+    /// Название для отображения
+    pub fn label(&self) -> &'static str {
+        match self {
+            TranslationKind::Voice => "Озвучка",
+            TranslationKind::Subtitles => "Субтитры",
+            TranslationKind::Unknown => "Неизвестно",
+        }
+    }
+}
+
 /// Структура результата медиа
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct MediaResult {
     pub title: String,
     pub title_orig: String,
     pub other_title: Option<String>,
+    /// Тип материала (`anime-serial`, `anime`, `foreign-serial` и т.д.)
+    #[serde(alias = "type")]
     pub media_type: Option<String>,
     pub year: i32,
     pub screenshots: Vec<String>,
@@ -48,9 +188,52 @@ pub struct MediaResult {
     pub kinopoisk_id: Option<String>,
     pub imdb_id: Option<String>,
     pub worldart_link: Option<String>,
-    pub additional_data: Option<HashMap<String, serde_json::Value>>,
-    pub material_data: Option<HashMap<String, serde_json::Value>>,
-    pub link: String
+    #[serde(default)]
+    pub translation: Option<ReleaseTranslation>,
+    #[serde(default)]
+    pub quality: Option<String>,
+    /// Количество серий в переводе (только для сериалов)
+    #[serde(default)]
+    pub episodes_count: Option<i32>,
+    #[serde(default)]
+    pub last_season: Option<i32>,
+    #[serde(default)]
+    pub last_episode: Option<i32>,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+    /// Метаданные тайтла, приходят при `with_material_data=true`
+    #[serde(default)]
+    pub material_data: Option<MaterialData>,
+    pub link: String,
+    /// Основной идентификатор тайтла, заполняется при форматировании результатов
+    #[serde(default)]
+    pub media_id: Option<MediaId>,
+    /// Все переводы тайтла, собранные при группировке результатов
+    #[serde(default)]
+    pub translations: Vec<AvailableTranslation>
+}
+
+impl MediaResult {
+    /// Выбрать идентификатор для запросов к Kodik: shikimori, затем kinopoisk, imdb и World Art
+    pub fn primary_id(&self) -> Option<MediaId> {
+        self.shikimori_id.as_ref().map(|id| MediaId::Shikimori(id.clone()))
+            .or_else(|| self.kinopoisk_id.as_ref().map(|id| MediaId::Kinopoisk(id.clone())))
+            .or_else(|| self.imdb_id.as_ref().map(|id| MediaId::Imdb(id.clone())))
+            .or_else(|| self.worldart_link.as_ref().map(|link| MediaId::WorldArt(link.clone())))
+    }
+
+    /// Перевод этого результата вместе с количеством серий и датой обновления
+    pub fn available_translation(&self) -> Option<AvailableTranslation> {
+        self.translation.as_ref().map(|translation| AvailableTranslation {
+            id: translation.id,
+            title: translation.title.clone(),
+            translation_type: translation.translation_type,
+            episodes_count: self.episodes_count,
+            last_season: self.last_season,
+            last_episode: self.last_episode,
+            updated_at: self.updated_at.clone(),
+        })
+    }
 }
 
 /// Структура данных медиа
@@ -61,9 +244,47 @@ pub struct MediaData {
 }
 
 
+/// Один уровень качества потока
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StreamQuality {
+    pub quality: i32,
+    pub hls_url: String,
+    pub mp4_url: String,
+}
+
+/// Описание потока со всеми качествами, которые вернул Kodik
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StreamInfo {
+    /// Качества, отсортированные по возрастанию
+    pub qualities: Vec<StreamQuality>,
+    /// Метка срока действия подписи из пути ссылки (как есть, например `2024011418`)
+    pub expires_hint: Option<String>,
+}
+
+impl StreamInfo {
+    /// Максимальное доступное качество
+    pub fn max_quality(&self) -> i32 {
+        self.qualities.iter().map(|q| q.quality).max().unwrap_or(0)
+    }
+
+    /// Выбрать качество по предпочтению пользователя.
+    ///
+    /// Берётся наибольшее качество, не превышающее `preferred`; если такого нет — минимальное.
+    /// Без предпочтения возвращается максимальное качество.
+    pub fn select(&self, preferred: Option<i32>) -> Option<&StreamQuality> {
+        match preferred {
+            Some(preferred) => self.qualities.iter()
+                .filter(|q| q.quality <= preferred)
+                .max_by_key(|q| q.quality)
+                .or_else(|| self.qualities.iter().min_by_key(|q| q.quality)),
+            None => self.qualities.iter().max_by_key(|q| q.quality),
+        }
+    }
+}
+
 impl std::fmt::Display for Translation {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} ({})", self.name, self.translation_type)
+        write!(f, "{} ({})", self.name, self.translation_type.label())
     }
 }
 
@@ -84,14 +305,132 @@ pub enum KodikError {
 
     #[error("Parser error: {0}")]
     ParserError(String),
+
+    /// Параметры поиска не имеют смысла (например, год из будущего)
+    #[error("Invalid query: {0}")]
+    InvalidQuery(String),
+
+    /// Разметка Kodik изменилась: селектор или регулярное выражение ничего не нашли
+    #[error("Layout changed at stage `{stage}`: `{selector}` matched nothing{}", snapshot_note(.snapshot))]
+    LayoutChanged {
+        stage: String,
+        selector: String,
+        /// Сохранённый фрагмент страницы, на которой произошёл сбой
+        snapshot: Option<PathBuf>,
+    },
+}
+
+fn snapshot_note(snapshot: &Option<PathBuf>) -> String {
+    snapshot.as_ref()
+        .map(|path| format!(" (snapshot: {})", path.display()))
+        .unwrap_or_default()
+}
+
+/// Каталог снимков страниц при смене разметки
+const SNAPSHOT_DIR: &str = "logs/kodik";
+/// Максимальный размер снимка
+const SNAPSHOT_LIMIT: usize = 64 * 1024;
+
+/// Сохранить обрезанный снимок страницы в каталог логов
+fn save_snapshot(stage: &str, selector: &str, page: &str) -> Option<PathBuf> {
+    let mut end = page.len().min(SNAPSHOT_LIMIT);
+    while !page.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    let path = Path::new(SNAPSHOT_DIR).join(format!(
+        "{}-{}.txt",
+        stage,
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    let content = format!("stage: {}\nselector: {}\n\n{}", stage, selector, &page[..end]);
+
+    match fs::create_dir_all(SNAPSHOT_DIR).and_then(|_| fs::write(&path, content)) {
+        Ok(()) => Some(path),
+        Err(e) => {
+            log::warn!("Failed to save Kodik page snapshot: {}", e);
+            None
+        }
+    }
+}
+
+/// Дополнить ошибку смены разметки снимком страницы
+fn with_snapshot<T>(result: std::result::Result<T, KodikError>, page: &str) -> std::result::Result<T, KodikError> {
+    result.map_err(|error| match error {
+        KodikError::LayoutChanged { stage, selector, snapshot: None } => {
+            let snapshot = save_snapshot(&stage, &selector, page);
+            let error = KodikError::LayoutChanged { stage, selector, snapshot };
+            log::error!("{}", error);
+            error
+        },
+        other => other,
+    })
+}
+
+/// Источник токена Kodik
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenSource {
+    /// Указан в настройках (.env)
+    Configured,
+    /// Получен со скрипта kodik-add.com
+    Scraped,
+}
+
+/// Сведения о токене, который сейчас используется
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenStatus {
+    pub token: String,
+    pub source: TokenSource,
+    pub candidates: usize,
+}
+
+/// Упорядоченный список токенов-кандидатов
+#[derive(Debug, Default)]
+struct TokenPool {
+    candidates: Vec<(String, TokenSource)>,
+    current: usize,
+    scraped: bool,
+}
+
+impl TokenPool {
+    fn current(&self) -> Option<&(String, TokenSource)> {
+        self.candidates.get(self.current)
+    }
+
+    /// Получить токен со скрипта Kodik и сделать его текущим (не более одного раза)
+    async fn scrape(&mut self, http: &KodikHttp) -> Result<bool> {
+        self.scrape_with(KodikParser::get_token(http)).await
+    }
+
+    /// Неудачная попытка не считается: токен будет запрошен снова при следующей ротации
+    async fn scrape_with(&mut self, fetch: impl Future<Output = Result<String>>) -> Result<bool> {
+        if self.scraped {
+            return Ok(false);
+        }
+
+        let token = fetch.await?;
+        self.scraped = true;
+        match self.candidates.iter().position(|(t, _)| *t == token) {
+            Some(index) if index > self.current => self.current = index,
+            Some(_) => return Ok(false),
+            None => {
+                self.candidates.push((token, TokenSource::Scraped));
+                self.current = self.candidates.len() - 1;
+            }
+        }
+        Ok(true)
+    }
 }
 
 /// Структура парсера Kodik
 #[derive(Debug, Clone)]
 pub struct KodikParser {
-    token: Option<String>,
+    tokens: Arc<Mutex<TokenPool>>,
     use_lxml: bool,
-    client: Client,
+    http: Arc<KodikHttp>,
+    cache: Arc<ResponseCache>,
+    prefetch: PrefetchConfig,
 }
 
 impl KodikParser {
@@ -101,34 +440,154 @@ impl KodikParser {
     /// * `token` - Токен Kodik для поиска по базе. Если не указан, будет предпринята попытка автоматического получения токена
     /// * `use_lxml` - Использовать парсер lxml (в некоторых случаях lxml может не работать)
     pub async fn new(token: Option<String>, use_lxml: bool) -> Result<Self> {
-        let token = match token {
-            Some(t) => Some(t),
-            None => {
-                Some(Self::get_token().await?)
-            }
-        };
+        let parser = Self::with_tokens(token.into_iter().collect(), use_lxml);
+        parser.active_token().await?;
+        Ok(parser)
+    }
+
+    /// Создать экземпляр KodikParser с упорядоченным списком токенов-кандидатов
+    ///
+    /// Пустые строки отбрасываются. Если ни один токен не подошёл, токен будет получен
+    /// автоматически при первом запросе.
+    pub fn with_tokens(tokens: Vec<String>, use_lxml: bool) -> Self {
+        let candidates = tokens.into_iter()
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .map(|t| (t, TokenSource::Configured))
+            .collect();
 
-        Ok(Self {
-            token,
+        Self {
+            tokens: Arc::new(Mutex::new(TokenPool {
+                candidates,
+                ..TokenPool::default()
+            })),
             use_lxml,
-            client: Client::new(),
-        })
+            http: Arc::new(KodikHttp::default()),
+            cache: Arc::new(ResponseCache::new(CacheConfig::default())),
+            prefetch: PrefetchConfig::default(),
+        }
+    }
+
+    /// Заменить политику HTTP-запросов (таймауты, повторы, ограничение частоты)
+    pub fn with_http_policy(mut self, policy: HttpPolicy) -> Self {
+        self.http = Arc::new(KodikHttp::new(policy));
+        self
+    }
+
+    /// Заменить настройки кэша ответов
+    pub fn with_cache(mut self, config: CacheConfig) -> Self {
+        self.cache = Arc::new(ResponseCache::new(config));
+        self
+    }
+
+    /// Заменить настройки предзагрузки серий
+    pub fn with_prefetch(mut self, config: PrefetchConfig) -> Self {
+        self.prefetch = config;
+        self
+    }
+
+    pub(super) fn prefetch_config(&self) -> &PrefetchConfig {
+        &self.prefetch
     }
 
     /// Создать пустой экземпляр KodikParser
     pub fn empty() -> Self {
-        Self {
-            token: None,
-            use_lxml: false,
-            client: Client::new(),
+        Self::with_tokens(Vec::new(), false)
+    }
+
+    /// Получить текущий токен (при отсутствии кандидатов токен будет получен автоматически)
+    pub async fn active_token(&self) -> Result<String> {
+        let mut pool = self.tokens.lock().await;
+        if pool.current().is_none() {
+            pool.scrape(&self.http).await?;
+        }
+        pool.current()
+            .map(|(token, _)| token.clone())
+            .ok_or_else(|| KodikError::TokenError("Токен kodik не указан".into()).into())
+    }
+
+    /// Сведения о текущем токене
+    pub async fn token_status(&self) -> Option<TokenStatus> {
+        let pool = self.tokens.lock().await;
+        pool.current().map(|(token, source)| TokenStatus {
+            token: token.clone(),
+            source: *source,
+            candidates: pool.candidates.len(),
+        })
+    }
+
+    /// Перейти к следующему токену после отказа в `rejected`.
+    ///
+    /// Возвращает `false`, если кандидатов больше нет.
+    async fn rotate_token(&self, rejected: &str) -> Result<bool> {
+        let mut pool = self.tokens.lock().await;
+
+        // Токен уже сменил параллельный запрос
+        if pool.current().map(|(token, _)| token.as_str()) != Some(rejected) {
+            return Ok(true);
+        }
+
+        if pool.current + 1 < pool.candidates.len() {
+            pool.current += 1;
+            log::warn!("Kodik token rejected, switching to candidate #{}", pool.current);
+            return Ok(true);
+        }
+
+        let rotated = pool.scrape(&self.http).await?;
+        if rotated {
+            log::warn!("Kodik token rejected, switched to scraped token");
+        }
+        Ok(rotated)
+    }
+
+    /// Выполнить запрос с текущим токеном.
+    /// При `KodikError::TokenError` запрос повторяется один раз со следующим токеном.
+    async fn with_token<T, F, Fut>(&self, request: F) -> Result<T>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let token = self.active_token().await?;
+
+        match request(token.clone()).await {
+            Err(e) if matches!(e.downcast_ref::<KodikError>(), Some(KodikError::TokenError(_))) => {
+                if self.rotate_token(&token).await? {
+                    request(self.active_token().await?).await
+                } else {
+                    Err(e)
+                }
+            }
+            result => result,
         }
     }
 
+    /// POST-запрос к API Kodik (`search`, `list`) с подстановкой токена
+    async fn post_api(&self, url: &str, payload: &[(&str, String)]) -> Result<SearchResponse> {
+        self.with_token(|token| async move {
+            let mut form = vec![("token", token)];
+            form.extend(payload.iter().cloned());
+
+            let response = self.http
+                .post_form("api", url, &form)
+                .await?
+                .json::<SearchResponse>()
+                .await?;
+
+            if let Some(error) = &response.error {
+                if error == "Отсутствует или неверный токен" {
+                    return Err(KodikError::TokenError("Отсутствует или неверный токен".into()).into());
+                }
+                return Err(KodikError::ServiceError(error.clone()).into());
+            }
+
+            Ok(response)
+        }).await
+    }
+
     /// Получить токен с Kodik
-    pub async fn get_token() -> Result<String> {
+    pub async fn get_token(http: &KodikHttp) -> Result<String> {
         let script_url = "https://kodik-add.com/add-players.min.js?v=2";
-        let response = reqwest::get(script_url).await.context("Не удалось выполнить запрос для получения токена")?;
-        let data = response.text().await.context("Не удалось прочитать ответ для токена")?;
+        let data = http.get_text("token", script_url).await.context("Не удалось выполнить запрос для получения токена")?;
         
         let token_start = data.find("token=").context("Не удалось найти начало токена")? + 6;
         let token_end = data[token_start..].find('"').context("Не удалось найти конец токена")?;
@@ -136,48 +595,32 @@ impl KodikParser {
         Ok(data[token_start..token_start + token_end].to_string())
     }
 
-    /// Вспомогательная функция для декодирования символов
-    fn convert_char(&self, char: char) -> char {
-        let is_lower = char.is_lowercase();
-        let alph = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
-        if alph.contains(char.to_ascii_uppercase()) {
-            let index = alph.find(char.to_ascii_uppercase()).unwrap();
-            let new_char = alph.chars().nth((index + 13) % alph.len()).unwrap();
-            if is_lower {
-                new_char.to_lowercase().next().unwrap()
-            } else {
-                new_char
-            }
-        } else {
-            char
-        }
+    /// Получить ссылку из информации (с кэшем)
+    pub(super) async fn _link_to_info(&self, id: &MediaId, https: bool) -> Result<String> {
+        let key = format!("{}:{}", id, https);
+        self.cache.get_or_fetch(CacheKind::PlayerLink, &key, || self.request_player_link(id, https)).await
     }
 
-    /// Декодировать строку со ссылкой
-    fn convert(&self, string: &str) -> String {
-        string.chars().map(|c| self.convert_char(c)).collect()
-    }
-
-    /// Получить ссылку из информации
-    async fn _link_to_info(&self, id: &str, id_type: &str, https: bool) -> Result<String> {
-        if let Some(token) = &self.token {
-            let serv = match id_type {
-                "shikimori" => format!(
-                    "https://kodikapi.com/get-player?title=Player&hasPlayer=false&url=https%3A%2F%2Fkodikdb.com%2Ffind-player%3FshikimoriID%3D{}&token={}&shikimoriID={}", 
-                    id, token, id
-                ),
-                "kinopoisk" => format!(
-                    "https://kodikapi.com/get-player?title=Player&hasPlayer=false&url=https%3A%2F%2Fkodikdb.com%2Ffind-player%3FkinopoiskID%3D{}&token={}&kinopoiskID={}", 
-                    id, token, id
-                ),
-                "imdb" => format!(
-                    "https://kodikapi.com/get-player?title=Player&hasPlayer=false&url=https%3A%2F%2Fkodikdb.com%2Ffind-player%3FkinopoiskID%3D{}&token={}&imdbID={}", 
-                    id, token, id
-                ),
-                _ => return Err(KodikError::InvalidIdType(id_type.to_string()).into()),
-            };
-
-            let data = self.client.get(&serv).send().await?.json::<serde_json::Value>().await?;
+    /// Запросить ссылку на плеер у `get-player` в обход кэша
+    pub(super) async fn request_player_link(&self, id: &MediaId, https: bool) -> Result<String> {
+        self.with_token(|token| async move {
+            let find_player = format!(
+                "https://kodikdb.com/find-player?{}={}",
+                id.player_param(),
+                id.encoded_value()
+            );
+            let serv = Url::parse_with_params(
+                "https://kodikapi.com/get-player",
+                &[
+                    ("title", "Player"),
+                    ("hasPlayer", "false"),
+                    ("url", find_player.as_str()),
+                    ("token", token.as_str()),
+                    (id.player_param(), id.value()),
+                ],
+            )?;
+
+            let data = self.http.get("get-player", serv.as_str()).await?.json::<serde_json::Value>().await?;
 
             if let Some(error) = data.get("error").and_then(|e| e.as_str()) {
                 match error {
@@ -189,7 +632,7 @@ impl KodikParser {
             }
 
             if !data.get("found").and_then(|f| f.as_bool()).unwrap_or(false) {
-                return Err(KodikError::NoResults(format!("Нет данных по {} id \"{}\"", id_type, id)).into());
+                return Err(KodikError::NoResults(format!("Нет данных по {} id \"{}\"", id.kind(), id.value())).into());
             }
 
             let link = data.get("link").and_then(|l| l.as_str()).unwrap_or("");
@@ -198,530 +641,293 @@ impl KodikParser {
             } else { 
                 format!("http:{}", link) 
             })
-        } else {
-            Err(KodikError::TokenError("Токен kodik не указан".into()).into())
-        }
+        }).await
     }
 
-    /// Проверить, является ли ссылка на сериал
-    fn is_serial(&self, iframe_url: &str) -> bool {
-        iframe_url.contains(".info/s")
+    /// Получить информацию: series_count, translations, seasons
+    pub async fn get_info(&self, id: &MediaId) -> Result<InfoResponse> {
+        self.cache.get_or_fetch(CacheKind::Info, &id.to_string(), || self.fetch_info(id)).await
     }
 
-    /// Проверить, является ли ссылка на видео
-    fn is_video(&self, iframe_url: &str) -> bool {
-        iframe_url.contains(".info/v")
-    }
-
-    /// Генерировать словарь переводов
-    fn generate_translations_dict(&self, translations_div: Option<Vec<scraper::element_ref::ElementRef>>) -> Vec<Translation> {
-        if let Some(options) = translations_div {
-            let mut translations = Vec::new();
-            for translation in options {
-                if let Some(id) = translation.value().attr("data-id") {
-                    let t_type = match translation.value().attr("data-translation-type") {
-                        Some("voice") => "Озвучка",
-                        Some("subtitles") => "Субтитры",
-                        _ => "Неизвестно",
-                    };
-                    let name = translation.text().collect::<Vec<_>>().join("");
-                    translations.push(Translation {
-                        id: id.to_string(),
-                        translation_type: t_type.to_string(),
-                        name,
-                    });
-                }
-            }
-            translations
-        } else {
-            vec![Translation {
-                id: "0".to_string(),
-                translation_type: "Неизвестно".to_string(),
-                name: "Неизвестно".to_string(),
-            }]
+    async fn fetch_info(&self, id: &MediaId) -> Result<InfoResponse> {
+        let link = self._link_to_info(id, true).await?;
+        let kind = PlayerKind::from_link(&link)
+            .ok_or_else(|| KodikError::ParserError("Ссылка на данные не была распознана как ссылка на сериал или фильм".into()))?;
+        let response = self.fetch_player_page(&link).await?;
+        let page = parsing::parse_player_page(&response, kind);
+
+        if kind == PlayerKind::Serial && page.episodes.is_empty() {
+            with_snapshot(Err(KodikError::LayoutChanged {
+                stage: "episodes".to_string(),
+                selector: parsing::EPISODES_SELECTOR.to_string(),
+                snapshot: None,
+            }), &response)?;
         }
-    }
 
-    /// Получить информацию: series_count, translations
-    pub async fn get_info(&self, id: &str, id_type: &str) -> Result<InfoResponse> {
-        let link = self._link_to_info(id, id_type, true).await?;
-        let response = self.client.get(&link).send().await?.text().await?;
-        let document = Html::parse_document(&response);
-
-        if self.is_serial(&link) {
-            // Для сериала
-            let series_count = document.select(&Selector::parse("div.serial-series-box select option").unwrap())
-                .count() as i32;
-
-            let translations_div = match document.select(&Selector::parse("div.serial-translations-box select").unwrap())
-                .next() {
-                Some(div) => {
-                    let options = div.select(&Selector::parse("option").unwrap()).collect::<Vec<_>>();
-                    Some(options)
-                },
-                None => None
-            };
-
-            Ok(InfoResponse {
-                series_count,
-                translations: self.generate_translations_dict(translations_div)
-            })
-        } else if self.is_video(&link) {
-            // Для фильма
-            let translations_div = match document.select(&Selector::parse("div.movie-translations-box select").unwrap())
-                .next() {
-                Some(div) => {
-                    let options = div.select(&Selector::parse("option").unwrap()).collect::<Vec<_>>();
-                    Some(options)
-                },
-                None => None
-            };
-
-            Ok(InfoResponse {
+        if kind == PlayerKind::Video {
+            return Ok(InfoResponse {
                 series_count: 0,
-                translations: self.generate_translations_dict(translations_div)
-            })
-        } else {
-            Err(KodikError::ParserError("Ссылка на данные не была распознана как ссылка на сериал или фильм".into()).into())
+                translations: page.translations,
+                seasons: Vec::new(),
+                episodes_translation: None
+            });
         }
-    }
 
-    /// Извлечение media_hash и media_id из контейнера переводов
-    fn extract_media_info<'a>(&self, container: &scraper::ElementRef<'a>, translation_id: &str) -> Result<(String, String)> {
-        let selector = Selector::parse("option").unwrap();
-        
-        for translation in container.select(&selector) {
-            if translation.value().attr("data-id") == Some(translation_id) {
-                let media_hash = translation.value().attr("data-media-hash")
-                    .ok_or_else(|| KodikError::ParserError("Не найден media-hash".into()))?;
-                    
-                let media_id = translation.value().attr("data-media-id")
-                    .ok_or_else(|| KodikError::ParserError("Не найден media-id".into()))?;
-                    
-                return Ok((media_hash.to_string(), media_id.to_string()));
-            }
-        }
-        
-        Err(KodikError::NoResults(format!("Перевод с id \"{}\" не найден.", translation_id)).into())
-    }
-
-    /// Получить ссылку на видео файл
-    pub async fn get_link(&self, id: &str, id_type: &str, seria_num: i32, translation_id: &str) -> Result<(String, i32)> {
-        let link = self._link_to_info(id, id_type, true).await?;
-        let response = self.client.get(&link).send().await?.text().await?;
-        
-        let (media_hash, media_id) = {
-            let document = Html::parse_document(&response);
-
-            if translation_id != "0" {
-                if seria_num != 0 {
-                    // Сериал с известной озвучкой на более чем 1 серию
-                    let container = document.select(&Selector::parse("div.serial-translations-box select").unwrap())
-                        .next()
-                        .ok_or_else(|| KodikError::ParserError("Не найден контейнер переводов сериала".into()))?;
-                    self.extract_media_info(&container, translation_id)?
-                } else {
-                    // Фильм/одна серия с несколькими переводами
-                    let container = document.select(&Selector::parse("div.movie-translations-box select").unwrap())
-                        .next()
-                        .ok_or_else(|| KodikError::ParserError("Не найден контейнер переводов фильма".into()))?;
-                    self.extract_media_info(&container, translation_id)?
+        let seasons = if page.seasons.len() <= 1 {
+            vec![Season {
+                number: page.seasons.first().copied().unwrap_or(1),
+                episodes: page.episodes,
+            }]
+        } else {
+            // Страница содержит серии только выбранного сезона, остальные подгружаем параллельно
+            let selected_season = page.selected_season.unwrap_or(page.seasons[0]);
+            let separator = if link.contains('?') { '&' } else { '?' };
+
+            futures::future::try_join_all(page.seasons.iter().map(|&number| {
+                let selected_episodes = (number == selected_season).then(|| page.episodes.clone());
+                let season_link = format!("{}{}season={}", link, separator, number);
+                async move {
+                    let episodes = match selected_episodes {
+                        Some(episodes) => episodes,
+                        None => parsing::parse_episodes(&self.fetch_player_page(&season_link).await?),
+                    };
+                    Ok::<_, anyhow::Error>(Season { number, episodes })
                 }
-            } else {
-                // Если translation_id == "0", используем данные из скрипта
-                let script_text = document.select(&Selector::parse("script").unwrap())
-                    .nth(4)
-                    .ok_or_else(|| KodikError::ParserError("Не найден скрипт с данными".into()))?
-                    .text()
-                    .collect::<String>();
-
-                let hash_regex = Regex::new(r"\.hash = '([^']+)'").unwrap();
-                let id_regex = Regex::new(r"\.id = '([^']+)'").unwrap();
-
-                let media_hash = hash_regex.captures(&script_text)
-                    .and_then(|c| c.get(1))
-                    .ok_or_else(|| KodikError::ParserError("Не найден hash в скрипте".into()))?
-                    .as_str()
-                    .to_string();
-
-                let media_id = id_regex.captures(&script_text)
-                    .and_then(|c| c.get(1))
-                    .ok_or_else(|| KodikError::ParserError("Не найден id в скрипте".into()))?
-                    .as_str()
-                    .to_string();
-
-                (media_hash, media_id)
-            }
+            })).await?
         };
+        let series_count = seasons.iter().map(|s| s.episodes.len() as i32).sum();
 
-        let url = if seria_num != 0 {
-            format!("https://kodik.info/serial/{}/{}/720p?min_age=16&first_url=false&season=1&episode={}", 
-                   media_id, media_hash, seria_num)
-        } else {
-            format!("https://kodik.info/video/{}/{}/720p?min_age=16&first_url=false", 
-                   media_id, media_hash)
-        };
+        Ok(InfoResponse {
+            series_count,
+            translations: page.translations,
+            seasons,
+            episodes_translation: page.selected_translation
+        })
+    }
 
-        let response = self.client.get(&url).send().await?.text().await?;
-        
-        let (script_url, script_text) = {
-            let document = Html::parse_document(&response);
-
-            let script_url = document.select(&Selector::parse("script").unwrap())
-                .nth(1)
-                .and_then(|el| el.value().attr("src"))
-                .ok_or_else(|| KodikError::ParserError("Не найден URL скрипта".into()))?
-                .to_string();
-
-            let script_text = document.select(&Selector::parse("script").unwrap())
-                .nth(4)
-                .ok_or_else(|| KodikError::ParserError("Не найден скрипт с данными".into()))?
-                .text()
-                .collect::<String>();
-
-            (script_url, script_text)
-        };
+    /// Получить ссылку на видео файл
+    pub async fn get_link(&self, id: &MediaId, season: i32, seria_num: i32, translation_id: &str) -> Result<StreamInfo> {
+        self.cached_stream(id, season, seria_num, translation_id).await
+    }
 
-        let type_regex = Regex::new(r"\.type = '([^']+)'").unwrap();
-        let hash_regex = Regex::new(r"\.hash = '([^']+)'").unwrap();
-        let id_regex = Regex::new(r"\.id = '([^']+)'").unwrap();
-
-        let video_type = type_regex.captures(&script_text)
-            .and_then(|c| c.get(1))
-            .ok_or_else(|| KodikError::ParserError("Не найден type в скрипте".into()))?
-            .as_str();
-
-        let video_hash = hash_regex.captures(&script_text)
-            .and_then(|c| c.get(1))
-            .ok_or_else(|| KodikError::ParserError("Не найден hash в скрипте".into()))?
-            .as_str();
-
-        let video_id = id_regex.captures(&script_text)
-            .and_then(|c| c.get(1))
-            .ok_or_else(|| KodikError::ParserError("Не найден id в скрипте".into()))?
-            .as_str();
-
-        let url_params_regex = Regex::new(r#"urlParams\s*=\s*'([^']+)'"#).unwrap();
-        let url_params_json = url_params_regex.captures(&response)
-            .and_then(|c| c.get(1))
-            .ok_or_else(|| KodikError::ParserError("Не найдены urlParams".into()))?
-            .as_str();
-
-        let url_params_value: serde_json::Value = serde_json::from_str(url_params_json)?;
-        let mut url_params = HashMap::new();
-        if let serde_json::Value::Object(map) = url_params_value {
-            for (key, value) in map {
-                let string_value = match value {
-                    serde_json::Value::Bool(b) => b.to_string(),
-                    serde_json::Value::Number(n) => n.to_string(), 
-                    serde_json::Value::String(s) => s,
-                    _ => value.to_string(),
-                };
-                url_params.insert(key, string_value);
-            }
-        }
+    /// Получить ссылку на скачивание
+    pub async fn get_download_link(
+        &self,
+        id: &MediaId,
+        season: i32,
+        seria_num: i32,
+        translation_id: &str,
+    ) -> Result<StreamInfo> {
+        self.cached_stream(id, season, seria_num, translation_id).await
+    }
 
-        let (link_data, max_quality) = self.get_link_with_data(
-            video_type,
-            video_hash,
-            video_id,
-            &url_params,
-            &script_url
-        ).await?;
+    /// Загрузить HLS-манифест качества и разобрать его варианты
+    ///
+    /// Если по ссылке сразу лежит media-плейлист, он возвращается единственным вариантом.
+    /// Ссылки подписаны и быстро истекают, поэтому манифест не кэшируется.
+    pub async fn get_hls_variants(&self, hls_url: &str) -> Result<MasterPlaylist> {
+        let manifest = self.fetch_page("hls", hls_url).await?;
+        Ok(match hls::parse(&manifest, hls_url)? {
+            HlsPlaylist::Master(master) => master,
+            HlsPlaylist::Media(_) => MasterPlaylist::single(hls_url),
+        })
+    }
 
-        let download_url = str::replace(&link_data, "https://", "");
-        let download_url = &download_url[2..download_url.len()-26]; // Remove :hls:manifest.m3u8
+    /// Забыть ссылки на поток серии, например когда подпись истекла раньше TTL кэша
+    pub fn invalidate_link(&self, id: &MediaId, season: i32, seria_num: i32, translation_id: &str) {
+        self.cache.invalidate(CacheKind::Stream, &Self::stream_key(id, season, seria_num, translation_id));
+    }
 
-        Ok((download_url.to_string(), max_quality))
+    fn stream_key(id: &MediaId, season: i32, seria_num: i32, translation_id: &str) -> String {
+        format!("{}:{}:{}:{}", id, season, seria_num, translation_id)
     }
-    
-    /// Вспомогательная функция для получения ссылки с данными
-    async fn get_link_with_data(
-        &self,
-        video_type: &str,
-        video_hash: &str,
-        video_id: &str,
-        url_params: &HashMap<String, String>,
-        script_url: &str
-    ) -> Result<(String, i32)> {
-        let params = [
-            ("hash", video_hash.to_string()),
-            ("id", video_id.to_string()),
-            ("type", video_type.to_string()),
-            ("d", url_params.get("d").cloned().unwrap_or_default()),
-            ("d_sign", url_params.get("d_sign").cloned().unwrap_or_default()),
-            ("pd", url_params.get("pd").cloned().unwrap_or_default()),
-            ("pd_sign", url_params.get("pd_sign").cloned().unwrap_or_default()),
-            ("ref", "".to_string()),
-            ("ref_sign", url_params.get("ref_sign").cloned().unwrap_or_default()),
-            ("bad_user", "true".to_string()),
-            ("cdn_is_working", "true".to_string()),
-        ];
 
-        let post_link = match self.get_post_link(script_url).await {
-            Ok(link) => link,
-            Err(e) => {
-                eprintln!("Ошибка при получении post_link: {}", e);
-                return Err(e);
-            }
-        };
+    /// Ссылки на поток с кэшем по тайтлу, серии и переводу
+    async fn cached_stream(&self, id: &MediaId, season: i32, seria_num: i32, translation_id: &str) -> Result<StreamInfo> {
+        let key = Self::stream_key(id, season, seria_num, translation_id);
+        self.cache.get_or_fetch(CacheKind::Stream, &key, || {
+            self.resolve_stream(id, season, seria_num, translation_id)
+        }).await
+    }
 
-        let post_url = format!("https://kodik.info{}", post_link);
+    /// Пройти цепочку страниц плеера до ссылок на поток
+    ///
+    /// `seria_num == 0` — фильм, `translation_id == "0"` — перевод по умолчанию со страницы плеера.
+    async fn resolve_stream(&self, id: &MediaId, season: i32, seria_num: i32, translation_id: &str) -> Result<StreamInfo> {
+        let link = self._link_to_info(id, true).await?;
 
-        let mut header_map = reqwest::header::HeaderMap::new();
-        header_map.insert(
-            reqwest::header::CONTENT_TYPE,
-            "application/x-www-form-urlencoded".parse().unwrap()
-        );
-
-        let response = match self.client.post(&post_url)
-            .headers(header_map)
-            .form(&params)
-            .send()
-            .await {
-                Ok(r) => match r.json::<serde_json::Value>().await {
-                    Ok(j) => j,
-                    Err(e) => {
-                        eprintln!("Ошибка при парсинге JSON ответа: {}", e);
-                        return Err(e.into());
-                    }
-                },
-                Err(e) => {
-                    eprintln!("Ошибка при отправке POST запроса: {}", e);
-                    return Err(e.into());
-                }
-            };
-
-        let url = self.convert(&response["links"]["360"][0]["src"].as_str().ok_or_else(|| {
-            KodikError::ParserError("Не удалось найти ссылку на 360p".into())
-        })?);
-
-        let max_quality = response["links"].as_object()
-            .ok_or_else(|| KodikError::ParserError("Не найдены данные о качестве".into()))?
-            .keys()
-            .filter_map(|k| k.parse::<i32>().ok())
-            .max()
-            .ok_or_else(|| KodikError::ParserError("Не удалось определить максимальное качество".into()))?;
-
-        let url_decoded = match base64::decode(url.as_bytes()) {
-            Ok(decoded) => String::from_utf8_lossy(&decoded).to_string(),
-            Err(_) => {
-                let padded = format!("{}==", url);
-                match base64::decode(padded.as_bytes()) {
-                    Ok(decoded) => String::from_utf8_lossy(&decoded).to_string().replace("https:", ""),
-                    Err(e) => {
-                        eprintln!("Ошибка при декодировании base64: {}", e);
-                        return Err(e.into());
-                    }
-                }
+        let mut player_page = None;
+        let url = match self.direct_episode_url(id, season, seria_num, translation_id).await {
+            Some(url) => url,
+            None => {
+                let page = self.fetch_player_page(&link).await?;
+                let url = Self::episode_page_url(&page, season, seria_num, translation_id)?;
+                player_page = Some(page);
+                url
             }
         };
 
-        Ok((url_decoded, max_quality))
+        let video_page = self.fetch_page("video_page", &url).await?;
+        // Страница плеера нужна, только если на странице серии нет urlParams
+        if player_page.is_none() && parsing::parse_url_params(&video_page).is_err() {
+            player_page = Some(self.fetch_player_page(&link).await?);
+        }
+        let (video_info, url_params, script_url) = Self::parse_video_page(&video_page, player_page.as_deref())?;
+
+        let post_link = self.get_post_link(&script_url).await?;
+        self.get_link_with_data(&video_info, &url_params, &post_link).await
     }
 
-    /// Получить ссылку на скачивание
-    pub async fn get_download_link(
-        &self,
-        id: &str,
-        id_type: &str,
-        seria_num: i32,
-        translation_id: &str,
-    ) -> Result<(String, i32)> {
-        // Проверка токена
-        let token = self.token.as_ref()
-            .context("Токен не установлен")?;
+    /// Загрузить страницу как текст (`stage` — этап цепочки для логов)
+    pub(super) async fn fetch_page(&self, stage: &str, url: &str) -> Result<String> {
+        self.http.get_text(stage, url).await
+    }
 
-        // Получение базовой ссылки на информацию
-        let base_url = self._link_to_info(id, id_type, true).await?;
-        
-        // Получение HTML страницы
-        let data = self.client.get(&base_url).send().await?.text().await?;
-
-        // Извлечение urlParams
-        let url_params_regex = Regex::new(r#"urlParams\s*=\s*'([^']+)'"#).unwrap();
-        let url_params = url_params_regex.captures(&data)
-            .and_then(|caps| caps.get(1))
-            .ok_or_else(|| KodikError::ParserError("Не удалось найти urlParams".into()))?;
-
-        let url_params_value: serde_json::Value = serde_json::from_str(url_params.as_str())?;
-        let mut url_params = HashMap::new();
-        if let serde_json::Value::Object(map) = url_params_value {
-            for (key, value) in map {
-                let string_value = match value {
-                    serde_json::Value::Bool(b) => b.to_string(),
-                    serde_json::Value::Number(n) => n.to_string(),
-                    serde_json::Value::String(s) => s,
-                    _ => value.to_string(),
-                };
-                url_params.insert(key, string_value);
-            }
-        }
+    /// Загрузить и отбросить первые `limit` байт по ссылке
+    pub(super) async fn fetch_prefix(&self, stage: &str, url: &str, limit: u64) -> Result<u64> {
+        self.http.get_prefix(stage, url, limit).await
+    }
 
-        // Получение media_hash и media_id в зависимости от типа контента
-        let (media_hash, media_id) = {
-            let document = Html::parse_document(&data);
-            let selector = if seria_num != 0 {
-                Selector::parse(".serial-translations-box select option").unwrap()
-            } else {
-                Selector::parse(".movie-translations-box select option").unwrap()
-            };
-
-            let mut found_hash = None;
-            let mut found_id = None;
-
-            for option in document.select(&selector) {
-                if option.value().attr("data-id") == Some(translation_id) {
-                    found_hash = option.value().attr("data-media-hash").map(|s| s.to_string());
-                    found_id = option.value().attr("data-media-id").map(|s| s.to_string());
-                    break;
-                }
-            }
+    /// Загрузить страницу плеера (iframe или сезон) с кэшем
+    async fn fetch_player_page(&self, url: &str) -> Result<String> {
+        self.cache.get_or_fetch(CacheKind::PlayerPage, url, || self.fetch_page("player_page", url)).await
+    }
 
-            match (found_hash, found_id) {
-                (Some(h), Some(i)) => (h, i),
-                _ => return Err(KodikError::ParserError("Не удалось найти media_hash/id".into()).into())
-            }
-        };
+    /// Прямая ссылка на серию по id и хэшу из `get_info`
+    ///
+    /// Серии на странице плеера относятся к одному переводу, поэтому для остальных
+    /// переводов (и для серий без id или хэша) возвращается `None` и ссылка ищется
+    /// через страницу плеера.
+    async fn direct_episode_url(&self, id: &MediaId, season: i32, seria_num: i32, translation_id: &str) -> Option<String> {
+        if seria_num == 0 {
+            return None;
+        }
 
-        if translation_id == "0" {
-            return Err(KodikError::ParserError("Требуется translation_id".into()).into());
+        let info = self.get_info(id).await.ok()?;
+        let episodes_translation = info.episodes_translation.as_deref()?;
+        if translation_id != "0" && translation_id != episodes_translation {
+            return None;
         }
 
-        // Формирование URL для получения видео
-        let video_url = if seria_num != 0 {
-            format!("https://kodik.info/serial/{}/{}/720p?min_age=16&first_url=false&season=1&episode={}", 
-                media_id, media_hash, seria_num)
+        info.seasons.iter()
+            .find(|s| s.number == season)?
+            .episode(seria_num)
+            .and_then(EpisodeInfo::page_url)
+    }
+
+    /// Ссылка на страницу серии (или фильма) для выбранного перевода
+    pub(super) fn episode_page_url(player_page: &str, season: i32, seria_num: i32, translation_id: &str) -> Result<String> {
+        let media = if translation_id == "0" {
+            with_snapshot(parsing::parse_video_info(player_page), player_page)?.media_ref()
         } else {
-            format!("https://kodik.info/video/{}/{}/720p?min_age=16&first_url=false", 
-                media_id, media_hash)
+            let kind = if seria_num != 0 { PlayerKind::Serial } else { PlayerKind::Video };
+            with_snapshot(parsing::find_translation(player_page, kind, translation_id), player_page)?
         };
 
-        // Получение данных видео
-        let video_data = self.client.get(&video_url).send().await?.text().await?;
-
-        // Получение script_url и параметров видео
-        let script_url = {
-            let video_document = Html::parse_document(&video_data);
-            video_document.select(&Selector::parse("script[src*='app.serial']").unwrap())
-                .next()
-                .and_then(|script| script.value().attr("src"))
-                .ok_or_else(|| KodikError::ParserError("Не удалось найти script_url".into()))?
-                .to_string()
-        };
+        Ok(if seria_num != 0 {
+            format!("https://kodik.info/serial/{}/{}/720p?min_age=16&first_url=false&season={}&episode={}",
+                   media.id, media.hash, season, seria_num)
+        } else {
+            format!("https://kodik.info/video/{}/{}/720p?min_age=16&first_url=false",
+                   media.id, media.hash)
+        })
+    }
 
-        // Извлечение параметров видео из скрипта
-        let script_text = {
-            let video_document = Html::parse_document(&video_data);
-            let scripts: Vec<_> = video_document.select(&Selector::parse("script").unwrap())
-                .map(|s| s.text().collect::<String>())
-                .collect();
-                
-            scripts.into_iter()
-                .find(|text| text.contains("videoInfo.type"))
-                .ok_or_else(|| KodikError::ParserError("Не найден скрипт с данными плеера".into()))?
+    /// Данные для запроса ссылок со страницы серии: `videoInfo`, `urlParams` и путь к скрипту плеера
+    ///
+    /// Если на странице серии нет `urlParams`, они берутся со страницы плеера `player_page`.
+    pub(super) fn parse_video_page(video_page: &str, player_page: Option<&str>) -> Result<(VideoInfo, HashMap<String, String>, String)> {
+        let video_info = with_snapshot(parsing::parse_video_info(video_page), video_page)?;
+        let script_url = with_snapshot(parsing::parse_player_script_url(video_page), video_page)?;
+        let url_params = match (parsing::parse_url_params(video_page), player_page) {
+            (Ok(params), _) => params,
+            (Err(_), Some(player_page)) => with_snapshot(parsing::parse_url_params(player_page), player_page)?,
+            (Err(e), None) => with_snapshot(Err(e), video_page)?,
         };
 
-        // Извлекаем значения напрямую из скрипта
-        let regex_type = Regex::new(r"videoInfo\.type\s*=\s*'([^']+)'").unwrap();
-        let regex_hash = Regex::new(r"videoInfo\.hash\s*=\s*'([^']+)'").unwrap();
-        let regex_id = Regex::new(r"videoInfo\.id\s*=\s*'([^']+)'").unwrap();
-
-        let video_type = regex_type.captures(&script_text)
-            .and_then(|caps| caps.get(1))
-            .map(|m| m.as_str().to_string())
-            .ok_or_else(|| KodikError::ParserError("Не удалось найти videoInfo.type".into()))?;
-
-        let video_hash = regex_hash.captures(&script_text)
-            .and_then(|caps| caps.get(1))
-            .map(|m| m.as_str().to_string())
-            .ok_or_else(|| KodikError::ParserError("Не удалось найти videoInfo.hash".into()))?;
-
-        let video_id = regex_id.captures(&script_text)
-            .and_then(|caps| caps.get(1))
-            .map(|m| m.as_str().to_string())
-            .ok_or_else(|| KodikError::ParserError("Не удалось найти videoInfo.id".into()))?;
-
-        // Получение финальной ссылки
-        let (download_url, quality) = self.get_link_with_data(
-            &video_type,
-            &video_hash,
-            &video_id,
-            &url_params,
-            &script_url
-        ).await?;
-
-        Ok((download_url[2..download_url.len()-26].to_string(), quality))
+        Ok((video_info, url_params, script_url))
     }
 
-    /// Вспомогательная функция для извлечения значения из скрипта
-    fn extract_value(script: &str, key: &str) -> Result<String> {
-        let regex = Regex::new(&format!(r#"{}\[^']*'([^']+)'"#, key)).unwrap();
-        regex.captures(script)
-            .and_then(|caps| caps.get(1))
-            .map(|m| m.as_str().to_string())
-            .ok_or_else(|| KodikError::ParserError(format!("Не удалось найти {}", key)).into())
-    }
+    /// Запросить ссылки на все качества у плеера
+    pub(super) async fn get_link_with_data(
+        &self,
+        video_info: &VideoInfo,
+        url_params: &HashMap<String, String>,
+        post_link: &str
+    ) -> Result<StreamInfo> {
+        let params = [
+            ("hash", video_info.hash.clone()),
+            ("id", video_info.id.clone()),
+            ("type", video_info.video_type.clone()),
+            ("d", url_params.get("d").cloned().unwrap_or_default()),
+            ("d_sign", url_params.get("d_sign").cloned().unwrap_or_default()),
+            ("pd", url_params.get("pd").cloned().unwrap_or_default()),
+            ("pd_sign", url_params.get("pd_sign").cloned().unwrap_or_default()),
+            ("ref", "".to_string()),
+            ("ref_sign", url_params.get("ref_sign").cloned().unwrap_or_default()),
+            ("bad_user", "true".to_string()),
+            ("cdn_is_working", "true".to_string()),
+        ];
 
-    /// Получить post_link из скрипта
-    async fn get_post_link(&self, script_url: &str) -> Result<String> {
-        let full_url = format!("https://kodik.info{}", script_url);
-        let response = self.client.get(&full_url).send().await?.text().await?;
-        
-        let ajax_start = response.find("$.ajax")
-            .ok_or_else(|| KodikError::ParserError("Не удалось найти $.ajax".into()))? + 30;
+        let post_url = format!("https://kodik.info{}", post_link);
 
-        let ajax_end = response[ajax_start..].find("cache:!1")
-            .ok_or_else(|| KodikError::ParserError("Не удалось найти конец $.ajax".into()))? - 3;
+        let text = self.http.post_form("post", &post_url, &params)
+            .await?
+            .text()
+            .await?;
 
-        let ajax_str = &response[ajax_start..ajax_start + ajax_end];
-        
-        let decoded = String::from_utf8(BASE64.decode(ajax_str.as_bytes())?)?;
+        let response: serde_json::Value = serde_json::from_str(&text)
+            .map_err(|e| KodikError::ParserError(format!("Некорректный ответ плеера: {}", e)))?;
+        Ok(with_snapshot(parsing::parse_stream_links(&response), &text)?)
+    }
 
-        Ok(decoded)
+    /// Получить post_link из скрипта плеера (с кэшем)
+    async fn get_post_link(&self, script_url: &str) -> Result<String> {
+        self.cache.get_or_fetch(CacheKind::PlayerScript, script_url, || self.fetch_post_link(script_url)).await
+    }
+
+    /// Загрузить скрипт плеера и извлечь post_link в обход кэша
+    pub(super) async fn fetch_post_link(&self, script_url: &str) -> Result<String> {
+        let full_url = format!("https://kodik.info{}", script_url);
+        let response = self.fetch_page("player_script", &full_url).await?;
+        Ok(with_snapshot(parsing::parse_post_link(&response), &response)?)
     }
 
     /// Поиск медиа по названию с базовым форматированием
     pub async fn search(
         &self,
-        title: &str,
-        limit: Option<i32>,
+        query: &SearchQuery,
         include_material_data: bool,
         anime_status: Option<String>,
         strict: bool,
         only_anime: bool,
     ) -> Result<Vec<MediaResult>> {
         let search_data = self.base_search(
-            title,
-            limit,
+            query,
             include_material_data,
             anime_status,
             strict
         ).await?;
         
-        Ok(self.prettify_data(&search_data.results, only_anime))
+        Ok(parsing::group_results(&search_data.results, only_anime))
     }
 
     /// Прямой поиск в базе Kodik
     pub async fn base_search(
         &self,
-        title: &str,
-        limit: Option<i32>,
+        query: &SearchQuery,
         include_material_data: bool,
         anime_status: Option<String>,
         strict: bool,
     ) -> Result<SearchResponse> {
-        let token = self.token.as_ref()
-            .context("Токен не установлен")?;
-
+        let title = query.title.as_str();
         let mut payload = vec![
-            ("token", token.clone()),
             ("title", if strict { format!("{} ", title) } else { title.to_string() }),
             ("with_material_data", if include_material_data { "true" } else { "false" }.to_string()),
             ("strict", if strict { "true" } else { "false" }.to_string()),
         ];
 
-        if let Some(limit_val) = limit {
+        if let Some(limit_val) = query.limit {
             payload.push(("limit", limit_val.to_string()));
         }
 
@@ -729,20 +935,9 @@ impl KodikParser {
             payload.push(("anime_status", status));
         }
 
-        let response = self.client
-            .post("https://kodikapi.com/search")
-            .form(&payload)
-            .send()
-            .await?
-            .json::<SearchResponse>()
-            .await?;
+        payload.extend(query.filter_fields()?);
 
-        if let Some(error) = &response.error {
-            if error == "Отсутствует или неверный токен" {
-                return Err(KodikError::TokenError("Отсутствует или неверный токен".into()).into());
-            }
-            return Err(KodikError::ServiceError(error.clone()).into());
-        }
+        let response = self.post_api("https://kodikapi.com/search", &payload).await?;
 
         if response.total == 0 {
             return Err(KodikError::NoResults(format!("По запросу \"{}\" ничего не найдено", title)).into());
@@ -760,94 +955,138 @@ impl KodikParser {
         anime_status: Option<String>,
         only_anime: bool,
         start_from: Option<String>,
+        sort: Option<String>,
     ) -> Result<(Vec<MediaResult>, Option<String>)> {
-        let token = self.token.as_ref()
-            .context("Токен не установлен")?;
-
         let mut results = Vec::new();
         let mut next_page = start_from;
 
         for _ in 0..pages_to_parse {
-            let mut payload = vec![
-                ("token", token.clone()),
-                ("limit", limit_per_page.to_string()),
-                ("with_material_data", if include_material_data { "true" } else { "false" }.to_string()),
-            ];
-
-            if let Some(status) = &anime_status {
-                payload.push(("anime_status", status.clone()));
+            let (page, next) = self.list_page(
+                limit_per_page,
+                include_material_data,
+                anime_status.as_deref(),
+                only_anime,
+                next_page.as_deref(),
+                sort.as_deref(),
+            ).await?;
+            results.extend(page);
+            next_page = next;
+
+            if next_page.is_none() {
+                break;
             }
+        }
 
-            if let Some(next) = &next_page {
-                payload.push(("next", next.clone()));
-            }
+        Ok((parsing::group_results(&results, only_anime), next_page))
+    }
 
-            let response = self.client
-                .post("https://kodikapi.com/list")
-                .form(&payload)
-                .send()
-                .await?
-                .json::<SearchResponse>()
-                .await?;
+    /// Одна страница `list` без группировки: результаты по переводам и курсор следующей страницы
+    ///
+    /// Пустой список — не ошибка: страница пуста, курсора нет.
+    async fn list_page(
+        &self,
+        limit: i32,
+        include_material_data: bool,
+        anime_status: Option<&str>,
+        only_anime: bool,
+        next: Option<&str>,
+        sort: Option<&str>,
+    ) -> Result<(Vec<MediaResult>, Option<String>)> {
+        let mut payload = vec![
+            ("limit", limit.to_string()),
+            ("with_material_data", if include_material_data { "true" } else { "false" }.to_string()),
+        ];
 
-            if let Some(error) = &response.error {
-                if error == "Отсутствует или неверный токен" {
-                    return Err(KodikError::TokenError("Отсутствует или неверный токен".into()).into());
-                }
-                return Err(KodikError::ServiceError(error.clone()).into());
-            }
+        if let Some(status) = anime_status {
+            payload.push(("anime_status", status.to_string()));
+        }
 
-            if response.total == 0 {
-                return Err(KodikError::NoResults(
-                    "Ничего не найдено. Скорее всего произошла ошибка, попробуйте позже или сообщите об ошибке на гитхабе.".into()
-                ).into());
-            }
+        if only_anime {
+            payload.push(("types", "anime-serial,anime".to_string()));
+        }
+
+        if let Some(sort) = sort {
+            payload.push(("sort", sort.to_string()));
+            payload.push(("order", "desc".to_string()));
+        }
 
-            // Обновить next_page
-            next_page = response.next_page_id.as_ref()
-                .and_then(|np| np.rfind('='))
-                .map(|idx| response.next_page_id.as_ref().unwrap()[idx + 1..].to_string());
+        if let Some(next) = next {
+            payload.push(("next", next.to_string()));
+        }
 
-            results.extend(response.results);
+        let response = self.post_api("https://kodikapi.com/list", &payload).await?;
+        if response.total == 0 {
+            return Ok((Vec::new(), None));
         }
 
-        Ok((self.prettify_data(&results, only_anime), next_page))
+        let next_page = response.next_page_id.as_deref().and_then(parsing::list_cursor);
+        Ok((response.results, next_page))
+    }
+
+    /// Страница каталога в заданном режиме
+    ///
+    /// Kodik отдаёт по строке на каждый перевод, поэтому страницы `list` догружаются,
+    /// пока после группировки не наберётся `limit` тайтлов (не больше `BROWSE_MAX_PAGES`
+    /// запросов). Курсор продолжает выдачу Kodik сразу за последней загруженной строкой:
+    /// строки не теряются и не повторяются, но тайтл, переводы которого попали на разные
+    /// страницы, встретится снова со своими оставшимися переводами.
+    ///
+    /// # Аргументы
+    /// * `mode` - Режим просмотра
+    /// * `limit` - Количество тайтлов на странице
+    /// * `cursor` - Курсор продолжения из предыдущей страницы
+    pub async fn browse(&self, mode: BrowseMode, limit: i32, cursor: Option<String>) -> Result<BrowsePage> {
+        let sort = match mode {
+            BrowseMode::RecentlyUpdated => Some("updated_at"),
+            _ => None,
+        };
+
+        let mut rows = Vec::new();
+        let mut results = Vec::new();
+        let mut next_cursor = cursor;
+        for _ in 0..BROWSE_MAX_PAGES {
+            let (page, next) = self.list_page(
+                limit,
+                true,
+                mode.anime_status(),
+                true,
+                next_cursor.as_deref(),
+                sort,
+            ).await?;
+            rows.extend(page);
+            next_cursor = next;
+
+            results = parsing::group_results(&rows, true);
+            if results.len() >= limit.max(0) as usize || next_cursor.is_none() {
+                break;
+            }
+        }
+
+        Ok(BrowsePage {
+            results,
+            next_cursor,
+        })
     }
 
     /// Поиск по ID с базовым форматированием
     pub async fn search_by_id(
         &self,
-        id: &str,
-        id_type: &str,
+        id: &MediaId,
         limit: Option<i32>,
     ) -> Result<Vec<MediaResult>> {
-        let search_data = self.base_search_by_id(id, id_type, limit, true).await?;
-        Ok(self.prettify_data(&search_data.results, false))
+        let search_data = self.base_search_by_id(id, limit, true).await?;
+        Ok(parsing::group_results(&search_data.results, false))
     }
 
     /// Прямой поиск по ID в базе Kodik
     pub async fn base_search_by_id(
         &self,
-        id: &str,
-        id_type: &str,
+        id: &MediaId,
         limit: Option<i32>,
         include_material_data: bool,
     ) -> Result<SearchResponse> {
-        if !["shikimori", "kinopoisk", "imdb"].contains(&id_type) {
-            return Err(KodikError::InvalidIdType(format!(
-                "Поддерживаются только id shikimori, kinopoisk, imdb. Получено: {}", 
-                id_type
-            )).into());
-        }
-
-        let token = self.token.as_ref()
-            .context("Токен не установлен")?;
-
-        let id_key = format!("{}_id", id_type);
-        let id_key = id_key.as_str();
         let mut payload = vec![
-            ("token", token.clone()),
-            (id_key, id.to_string()),
+            (id.search_param(), id.value().to_string()),
             ("with_material_data", if include_material_data { "true" } else { "false" }.to_string()),
         ];
 
@@ -855,110 +1094,130 @@ impl KodikParser {
             payload.push(("limit", limit_val.to_string()));
         }
 
-        let response = self.client
-            .post("https://kodikapi.com/search")
-            .form(&payload)
-            .send()
-            .await?
-            .json::<SearchResponse>()
-            .await?;
-
-        if let Some(error) = &response.error {
-            match error.as_str() {
-                "Отсутствует или неверный токен" => {
-                    return Err(KodikError::TokenError("Отсутствует или неверный токен".into()).into());
-                },
-                _ => return Err(KodikError::ServiceError(error.clone()).into()),
-            }
-        }
+        let response = self.post_api("https://kodikapi.com/search", &payload).await?;
 
         if response.total == 0 {
-            return Err(KodikError::NoResults(format!("По id {} \"{}\" ничего не найдено", id_type, id)).into());
+            return Err(KodikError::NoResults(format!("По id {} \"{}\" ничего не найдено", id.kind(), id.value())).into());
         }
 
         Ok(response)
     }
 
-    /// Вспомогательная функция для форматирования данных
-    fn prettify_data(&self, results: &[MediaResult], only_anime: bool) -> Vec<MediaResult> {
-        let mut data = Vec::new();
-        let mut added_titles = Vec::new();
-
-        for result in results {
-            if only_anime {
-                if let Some(media_type) = &result.media_type {
-                    if !["anime-serial", "anime"].contains(&media_type.as_str()) {
-                        continue;
-                    }
-                }
-            }
+    /// Переводы тайтла с текущим числом серий, напрямую из `search` (без кэша)
+    pub async fn available_translations(&self, id: &MediaId) -> Result<Vec<AvailableTranslation>> {
+        let search_data = self.base_search_by_id(id, Some(100), false).await?;
 
-            if !added_titles.contains(&result.title) {
-                data.push(result.clone());
-                added_titles.push(result.title.clone());
+        let mut translations: Vec<AvailableTranslation> = Vec::new();
+        for translation in search_data.results.iter().filter_map(MediaResult::available_translation) {
+            if !translations.iter().any(|t| t.id == translation.id) {
+                translations.push(translation);
             }
         }
-
-        data
+        Ok(translations)
     }
 
     /// Получить переводы для медиа по ID
-    pub async fn translations(&self, id: &str, id_type: &str) -> Result<Vec<Translation>> {
-        let info = self.get_info(id, id_type).await?;
+    pub async fn translations(&self, id: &MediaId) -> Result<Vec<Translation>> {
+        let info = self.get_info(id).await?;
         Ok(info.translations)
     }
 
     /// Получить количество серий для медиа по ID
-    pub async fn series_count(&self, id: &str, id_type: &str) -> Result<i32> {
-        let info = self.get_info(id, id_type).await?;
+    pub async fn series_count(&self, id: &MediaId) -> Result<i32> {
+        let info = self.get_info(id).await?;
         Ok(info.series_count)
     }
 
     /// Получить ссылку до страницы с данными
-    pub async fn get_info_full(&self, id: &str, id_type: &str) -> Result<InfoResponse> {
-        self.get_info(id, id_type).await
+    pub async fn get_info_full(&self, id: &MediaId) -> Result<InfoResponse> {
+        self.get_info(id).await
     }
 }
 
-/*     // Initialize KodikParser
-    let parser = KodikParser::new(Some("447d179e875efe44217f20d1ee2146be".to_string()), false).await?;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    // Search for title and get first result
-    let search_results = parser.search(title, Some(10), true, None, false, true).await?;
-    println!("search_results: {:?}", search_results);
+    #[tokio::test]
+    async fn failed_scrape_is_retried() {
+        let mut pool = TokenPool::default();
 
-    // Find best matching title using fuzzy search
-    let best_match = search_results.iter()
-        .max_by_key(|result| {
-            let similarity = strsim::jaro_winkler(
-                &result.title.to_lowercase(),
-                &title.to_lowercase()
-            );
-            (similarity * 100.0) as i32
-        })
-        .ok_or_else(|| anyhow::anyhow!("No results found"))?;
-
-    println!("Best match: {}", best_match.title);
-    
-    // Get shikimori ID and series count
-    let shikimori_id = best_match.shikimori_id.as_ref()
-        .ok_or_else(|| anyhow::anyhow!("No shikimori ID found"))?;
-    let info = parser.get_info(shikimori_id, "shikimori").await?;
-    println!("info: {:?}", info);
-    
-    // Create m3u8 playlist
-    let mut playlist = String::from("#EXTM3U\n");
-    
-    // Add each episode to playlist
-    let episode_count = if info.series_count > 0 { info.series_count } else { 1 };
-    for episode in 1..=episode_count {
-        let (download_link, quality) = parser.get_link(shikimori_id, "shikimori", episode, translation_id).await?;
-        println!("download_link: {}, quality: {}", download_link, quality);
-        let download_link = format!("https://{}/720.mp4/", download_link);
-        playlist.push_str(&format!("#EXTINF:-1,Episode {}\n{}\n", episode, download_link));
-    }
-
-    // Write playlist to file
-    let filename = format!("{}.m3u8", title.replace(" ", "_"));
-    std::fs::write(&filename, playlist)?;
-    println!("Created playlist: {}", filename); */
\ No newline at end of file
+        let failed = pool.scrape_with(async { Err(anyhow::anyhow!("timeout")) }).await;
+        assert!(failed.is_err());
+        assert!(pool.current().is_none());
+
+        assert!(pool.scrape_with(async { Ok("scraped".to_string()) }).await.unwrap());
+        assert_eq!(pool.current(), Some(&("scraped".to_string(), TokenSource::Scraped)));
+
+        // После успешной попытки скрипт больше не запрашивается
+        assert!(!pool.scrape_with(async { Ok("other".to_string()) }).await.unwrap());
+    }
+
+    /// Парсер с настроенными токенами; скрипт Kodik считается уже опрошенным,
+    /// чтобы тесты не ходили в сеть
+    async fn parser(tokens: &[&str]) -> KodikParser {
+        let parser = KodikParser::with_tokens(tokens.iter().map(|t| t.to_string()).collect(), false);
+        parser.tokens.lock().await.scraped = true;
+        parser
+    }
+
+    fn token_error() -> anyhow::Error {
+        KodikError::TokenError("Отсутствует или неверный токен".into()).into()
+    }
+
+    #[tokio::test]
+    async fn rotate_over_candidates() {
+        let parser = parser(&["a", "b", "c"]).await;
+
+        assert!(parser.rotate_token("a").await.unwrap());
+        assert_eq!(parser.active_token().await.unwrap(), "b");
+        assert!(parser.rotate_token("b").await.unwrap());
+        assert_eq!(parser.active_token().await.unwrap(), "c");
+
+        // Кандидаты кончились, а скрипт уже опрошен
+        assert!(!parser.rotate_token("c").await.unwrap());
+        assert_eq!(parser.active_token().await.unwrap(), "c");
+    }
+
+    #[tokio::test]
+    async fn rotate_after_concurrent_rotation() {
+        let parser = parser(&["a", "b", "c"]).await;
+        assert!(parser.rotate_token("a").await.unwrap());
+
+        // Второй запрос с тем же отклонённым токеном не пропускает кандидата
+        assert!(parser.rotate_token("a").await.unwrap());
+        assert_eq!(parser.active_token().await.unwrap(), "b");
+    }
+
+    #[tokio::test]
+    async fn with_token_retries_once() {
+        let parser = parser(&["a", "b", "c"]).await;
+        let used = Mutex::new(Vec::new());
+
+        let result: Result<()> = parser.with_token(|token| {
+            let used = &used;
+            async move {
+                used.lock().await.push(token);
+                Err(token_error())
+            }
+        }).await;
+
+        assert!(matches!(result.unwrap_err().downcast_ref::<KodikError>(), Some(KodikError::TokenError(_))));
+        assert_eq!(*used.lock().await, vec!["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn with_token_succeeds_with_next_candidate() {
+        let parser = parser(&["a", "b"]).await;
+
+        let result = parser.with_token(|token| async move {
+            if token == "a" { Err(token_error()) } else { Ok(token) }
+        }).await;
+        assert_eq!(result.unwrap(), "b");
+
+        // Другие ошибки не приводят к смене токена
+        let result: Result<()> = parser.with_token(|_| async { Err(KodikError::ServiceError("down".into()).into()) }).await;
+        assert!(result.is_err());
+        assert_eq!(parser.active_token().await.unwrap(), "b");
+    }
+}
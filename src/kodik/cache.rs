@@ -0,0 +1,312 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use anyhow::Result;
+use directories::ProjectDirs;
+use parking_lot::Mutex;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// Тип кэшируемого ответа Kodik, у каждого свой TTL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CacheKind {
+    /// Ссылка на плеер из `get-player`
+    PlayerLink,
+    /// HTML страницы плеера (iframe и страницы сезонов)
+    PlayerPage,
+    /// Разобранная информация о тайтле (переводы, сезоны)
+    Info,
+    /// Скрипт плеера с адресом POST-запроса
+    PlayerScript,
+    /// Ссылки на поток для серии и перевода
+    Stream,
+}
+
+impl CacheKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            CacheKind::PlayerLink => "player_link",
+            CacheKind::PlayerPage => "player_page",
+            CacheKind::Info => "info",
+            CacheKind::PlayerScript => "player_script",
+            CacheKind::Stream => "stream",
+        }
+    }
+}
+
+/// Настройки кэша ответов Kodik
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    pub player_link_ttl: Duration,
+    pub player_page_ttl: Duration,
+    pub info_ttl: Duration,
+    pub player_script_ttl: Duration,
+    /// Ссылки на поток подписаны и быстро истекают, поэтому TTL короткий
+    pub stream_ttl: Duration,
+    /// Каталог дискового кэша; `None` — только память
+    pub disk_dir: Option<PathBuf>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            player_link_ttl: Duration::from_secs(24 * 60 * 60),
+            player_page_ttl: Duration::from_secs(60 * 60),
+            info_ttl: Duration::from_secs(60 * 60),
+            player_script_ttl: Duration::from_secs(24 * 60 * 60),
+            stream_ttl: Duration::from_secs(10 * 60),
+            disk_dir: ProjectDirs::from("com", "kamiview", "KamiView")
+                .map(|dirs| dirs.cache_dir().join("kodik")),
+        }
+    }
+}
+
+impl CacheConfig {
+    /// Настройки по умолчанию с переопределением TTL (в секундах) из окружения:
+    /// `KODIK_CACHE_TTL_PLAYER_LINK`, `KODIK_CACHE_TTL_PLAYER_PAGE`, `KODIK_CACHE_TTL_INFO`,
+    /// `KODIK_CACHE_TTL_PLAYER_SCRIPT`, `KODIK_CACHE_TTL_STREAM`. `KODIK_CACHE_DISK=0` отключает диск.
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        for kind in [CacheKind::PlayerLink, CacheKind::PlayerPage, CacheKind::Info, CacheKind::PlayerScript, CacheKind::Stream] {
+            let var = format!("KODIK_CACHE_TTL_{}", kind.as_str().to_uppercase());
+            if let Some(secs) = std::env::var(&var).ok().and_then(|v| v.trim().parse::<u64>().ok()) {
+                *config.ttl_mut(kind) = Duration::from_secs(secs);
+            }
+        }
+        if std::env::var("KODIK_CACHE_DISK").map(|v| v == "0").unwrap_or(false) {
+            config.disk_dir = None;
+        }
+        config
+    }
+
+    pub fn ttl(&self, kind: CacheKind) -> Duration {
+        match kind {
+            CacheKind::PlayerLink => self.player_link_ttl,
+            CacheKind::PlayerPage => self.player_page_ttl,
+            CacheKind::Info => self.info_ttl,
+            CacheKind::PlayerScript => self.player_script_ttl,
+            CacheKind::Stream => self.stream_ttl,
+        }
+    }
+
+    fn ttl_mut(&mut self, kind: CacheKind) -> &mut Duration {
+        match kind {
+            CacheKind::PlayerLink => &mut self.player_link_ttl,
+            CacheKind::PlayerPage => &mut self.player_page_ttl,
+            CacheKind::Info => &mut self.info_ttl,
+            CacheKind::PlayerScript => &mut self.player_script_ttl,
+            CacheKind::Stream => &mut self.stream_ttl,
+        }
+    }
+}
+
+/// Запись кэша: значение в JSON и время истечения (секунды UNIX)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    expires_at: u64,
+    value: serde_json::Value,
+}
+
+impl CacheEntry {
+    fn is_fresh(&self) -> bool {
+        self.expires_at > unix_now()
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+type CacheKey = (CacheKind, String);
+
+/// Запрос, который сейчас выполняется; внутри — ошибка, если он не удался
+type InFlight = Arc<tokio::sync::Mutex<Option<Arc<anyhow::Error>>>>;
+
+/// Ошибка запроса, которую получили и другие ожидавшие его вызовы
+///
+/// Сообщение и цепочка причин (`source`) те же, что у исходной ошибки;
+/// сама она доступна через [`SharedError::inner`].
+#[derive(Debug, Clone)]
+pub struct SharedError(Arc<anyhow::Error>);
+
+impl SharedError {
+    pub fn inner(&self) -> &anyhow::Error {
+        &self.0
+    }
+}
+
+impl fmt::Display for SharedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&*self.0, f)
+    }
+}
+
+impl std::error::Error for SharedError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0.source()
+    }
+}
+
+/// Кэш ответов Kodik в памяти и на диске с объединением одинаковых запросов
+///
+/// Пока один запрос по ключу выполняется, остальные ждут его результата вместо
+/// повторного обращения к Kodik. Если запрос не удался, уже ожидавшие получают
+/// [`SharedError`] с той же ошибкой, а пришедшие позже делают новую попытку.
+#[derive(Debug, Default)]
+pub struct ResponseCache {
+    config: CacheConfig,
+    memory: Mutex<HashMap<CacheKey, CacheEntry>>,
+    in_flight: Mutex<HashMap<CacheKey, InFlight>>,
+}
+
+impl ResponseCache {
+    pub fn new(config: CacheConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    /// Получить значение из кэша или вычислить его через `fetch`
+    pub async fn get_or_fetch<T, F, Fut>(&self, kind: CacheKind, key: &str, fetch: F) -> Result<T>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let cache_key = (kind, key.to_string());
+        if let Some(value) = self.lookup(&cache_key).await {
+            return Ok(value);
+        }
+
+        let in_flight = self.in_flight.lock()
+            .entry(cache_key.clone())
+            .or_default()
+            .clone();
+        let mut failure = in_flight.lock().await;
+
+        // Пока ждали, значение мог получить другой запрос — или не получить
+        if let Some(value) = self.lookup(&cache_key).await {
+            return Ok(value);
+        }
+        if let Some(error) = failure.as_ref() {
+            return Err(SharedError(error.clone()).into());
+        }
+
+        let result = fetch().await;
+        if let Ok(value) = &result {
+            self.store(&cache_key, value).await;
+        }
+        self.finish(&cache_key, &in_flight);
+
+        match result {
+            Ok(value) => Ok(value),
+            // Никто не ждёт: ошибка возвращается как есть
+            Err(e) if Arc::strong_count(&in_flight) == 1 => Err(e),
+            Err(e) => {
+                let error = Arc::new(e);
+                *failure = Some(error.clone());
+                Err(SharedError(error).into())
+            }
+        }
+    }
+
+    /// Убрать запись о завершённом запросе: следующие вызовы начнут новый,
+    /// а результат достанется только тем, кто уже держит ссылку на него
+    fn finish(&self, cache_key: &CacheKey, in_flight: &InFlight) {
+        let mut map = self.in_flight.lock();
+        if map.get(cache_key).is_some_and(|entry| Arc::ptr_eq(entry, in_flight)) {
+            map.remove(cache_key);
+        }
+    }
+
+    /// Удалить значение из памяти и с диска
+    pub fn invalidate(&self, kind: CacheKind, key: &str) {
+        let cache_key = (kind, key.to_string());
+        self.memory.lock().remove(&cache_key);
+        if let Some(path) = self.disk_path(&cache_key) {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    async fn lookup<T: DeserializeOwned>(&self, cache_key: &CacheKey) -> Option<T> {
+        let entry = {
+            let mut memory = self.memory.lock();
+            match memory.get(cache_key) {
+                Some(entry) if entry.is_fresh() => Some(entry.clone()),
+                Some(_) => {
+                    memory.remove(cache_key);
+                    None
+                },
+                None => None,
+            }
+        };
+
+        let entry = match entry {
+            Some(entry) => entry,
+            None => {
+                let path = self.disk_path(cache_key)?;
+                let entry = tokio::task::spawn_blocking(move || read_disk(&path)).await.ok()??;
+                self.memory.lock().insert(cache_key.clone(), entry.clone());
+                entry
+            }
+        };
+
+        serde_json::from_value(entry.value).ok()
+    }
+
+    async fn store<T: Serialize>(&self, cache_key: &CacheKey, value: &T) {
+        let ttl = self.config.ttl(cache_key.0);
+        if ttl.is_zero() {
+            return;
+        }
+        let value = match serde_json::to_value(value) {
+            Ok(value) => value,
+            Err(_) => return,
+        };
+
+        let entry = CacheEntry {
+            expires_at: unix_now() + ttl.as_secs(),
+            value,
+        };
+        self.memory.lock().insert(cache_key.clone(), entry.clone());
+        if let Some(path) = self.disk_path(cache_key) {
+            let _ = tokio::task::spawn_blocking(move || write_disk(&path, &entry)).await;
+        }
+    }
+
+    fn disk_path(&self, (kind, key): &CacheKey) -> Option<PathBuf> {
+        let dir = self.config.disk_dir.as_ref()?;
+        let name: String = key.chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        // Имя файла обрезается, поэтому добавляем хэш полного ключа
+        let hash = key.bytes().fold(0xcbf29ce484222325u64, |h, b| (h ^ b as u64).wrapping_mul(0x100000001b3));
+        let name = name.chars().take(80).collect::<String>();
+        Some(dir.join(kind.as_str()).join(format!("{}-{:016x}.json", name, hash)))
+    }
+}
+
+fn read_disk(path: &Path) -> Option<CacheEntry> {
+    let data = fs::read_to_string(path).ok()?;
+    let entry: CacheEntry = serde_json::from_str(&data).ok()?;
+    if entry.is_fresh() {
+        Some(entry)
+    } else {
+        let _ = fs::remove_file(path);
+        None
+    }
+}
+
+fn write_disk(path: &Path, entry: &CacheEntry) {
+    let result = path.parent()
+        .map(fs::create_dir_all)
+        .unwrap_or(Ok(()))
+        .and_then(|_| fs::write(path, serde_json::to_string(entry).unwrap_or_default()));
+    if let Err(e) = result {
+        log::warn!("Failed to write Kodik cache entry {}: {}", path.display(), e);
+    }
+}
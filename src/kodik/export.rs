@@ -0,0 +1,194 @@
+use std::fmt::Write;
+use std::path::Path;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use super::playlist::{Playlist, PlaylistEntry};
+
+/// Формат экспорта плейлиста
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlaylistFormat {
+    M3u8,
+    Xspf,
+    Pls,
+    /// mpv EDL: все серии склеиваются в один файл с главами
+    Edl,
+}
+
+impl PlaylistFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            PlaylistFormat::M3u8 => "m3u8",
+            PlaylistFormat::Xspf => "xspf",
+            PlaylistFormat::Pls => "pls",
+            PlaylistFormat::Edl => "edl",
+        }
+    }
+
+    /// Определить формат по расширению файла
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "m3u8" | "m3u" => Some(PlaylistFormat::M3u8),
+            "xspf" => Some(PlaylistFormat::Xspf),
+            "pls" => Some(PlaylistFormat::Pls),
+            "edl" => Some(PlaylistFormat::Edl),
+            _ => None,
+        }
+    }
+}
+
+impl Playlist {
+    /// Плейлист в выбранном формате
+    pub fn export(&self, format: PlaylistFormat) -> String {
+        match format {
+            PlaylistFormat::M3u8 => self.to_m3u8(),
+            PlaylistFormat::Xspf => self.to_xspf(),
+            PlaylistFormat::Pls => self.to_pls(),
+            PlaylistFormat::Edl => self.to_edl(),
+        }
+    }
+
+    /// Записать плейлист в файл
+    pub fn write_to(&self, path: &Path, format: PlaylistFormat) -> Result<()> {
+        std::fs::write(path, self.export(format))
+            .map_err(|e| anyhow::anyhow!("Failed to write playlist to {}: {}", path.display(), e))
+    }
+
+    /// M3U8 с длительностями и заголовками в `#EXTVLCOPT`;
+    /// неполученные серии остаются комментариями
+    pub fn to_m3u8(&self) -> String {
+        let mut playlist = String::from("#EXTM3U\n");
+        let _ = writeln!(playlist, "#PLAYLIST:{}", single_line(&self.title));
+
+        for entry in &self.entries {
+            let Some(url) = &entry.url else {
+                let _ = writeln!(
+                    playlist,
+                    "# {}: failed to resolve ({})",
+                    single_line(&entry.name),
+                    single_line(entry.error.as_deref().unwrap_or("unknown error"))
+                );
+                continue;
+            };
+
+            let _ = writeln!(playlist, "#EXTINF:{},{}", m3u_duration(entry), self.entry_title(entry));
+            if let Some(user_agent) = &self.headers.user_agent {
+                let _ = writeln!(playlist, "#EXTVLCOPT:http-user-agent={}", single_line(user_agent));
+            }
+            if let Some(referer) = &self.headers.referer {
+                let _ = writeln!(playlist, "#EXTVLCOPT:http-referrer={}", single_line(referer));
+            }
+            let _ = writeln!(playlist, "{}", single_line(url));
+        }
+        playlist
+    }
+
+    /// XSPF; заголовки передаются как опции VLC
+    pub fn to_xspf(&self) -> String {
+        let mut playlist = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        playlist.push_str("<playlist version=\"1\" xmlns=\"http://xspf.org/ns/0/\" xmlns:vlc=\"http://www.videolan.org/vlc/playlist/ns/0/\">\n");
+        let _ = writeln!(playlist, "  <title>{}</title>", xml_escape(&self.title));
+        playlist.push_str("  <trackList>\n");
+
+        for entry in self.resolved() {
+            let url = entry.url.as_deref().unwrap_or_default();
+            playlist.push_str("    <track>\n");
+            let _ = writeln!(playlist, "      <location>{}</location>", xml_escape(url));
+            let _ = writeln!(playlist, "      <title>{}</title>", xml_escape(&entry.name));
+            let _ = writeln!(playlist, "      <album>{}</album>", xml_escape(&self.title));
+            if let Some(duration) = entry.duration {
+                let _ = writeln!(playlist, "      <duration>{}</duration>", u64::from(duration) * 1000);
+            }
+
+            let options = self.vlc_options();
+            if !options.is_empty() {
+                playlist.push_str("      <extension application=\"http://www.videolan.org/vlc/playlist/0\">\n");
+                for option in options {
+                    let _ = writeln!(playlist, "        <vlc:option>{}</vlc:option>", xml_escape(&option));
+                }
+                playlist.push_str("      </extension>\n");
+            }
+            playlist.push_str("    </track>\n");
+        }
+
+        playlist.push_str("  </trackList>\n</playlist>\n");
+        playlist
+    }
+
+    /// PLS; формат не поддерживает заголовки
+    pub fn to_pls(&self) -> String {
+        let mut playlist = String::from("[playlist]\n");
+        let mut count = 0;
+
+        for entry in self.resolved() {
+            count += 1;
+            let _ = writeln!(playlist, "File{}={}", count, single_line(entry.url.as_deref().unwrap_or_default()));
+            let _ = writeln!(playlist, "Title{}={}", count, self.entry_title(entry));
+            let _ = writeln!(playlist, "Length{}={}", count, m3u_duration(entry));
+        }
+
+        let _ = writeln!(playlist, "NumberOfEntries={}", count);
+        playlist.push_str("Version=2\n");
+        playlist
+    }
+
+    /// mpv EDL: серии идут подряд, каждая отдельной главой. Заголовки в EDL
+    /// не задаются, их нужно передать mpv (`--user-agent`, `--referrer`)
+    pub fn to_edl(&self) -> String {
+        let mut playlist = String::from("# mpv EDL v0\n");
+
+        for entry in self.resolved() {
+            let url = entry.url.as_deref().unwrap_or_default();
+            let _ = write!(playlist, "{},title={}", edl_value(url), edl_value(&entry.name));
+            if let Some(duration) = entry.duration {
+                let _ = write!(playlist, ",length={}", duration);
+            }
+            playlist.push('\n');
+        }
+        playlist
+    }
+
+    fn resolved(&self) -> impl Iterator<Item = &PlaylistEntry> {
+        self.entries.iter().filter(|e| e.is_resolved())
+    }
+
+    /// Название записи для построчных форматов (M3U8, PLS)
+    fn entry_title(&self, entry: &PlaylistEntry) -> String {
+        single_line(&format!("{} - {}", self.title, entry.name))
+    }
+
+    fn vlc_options(&self) -> Vec<String> {
+        let mut options = Vec::new();
+        if let Some(user_agent) = &self.headers.user_agent {
+            options.push(format!("http-user-agent={}", user_agent));
+        }
+        if let Some(referer) = &self.headers.referer {
+            options.push(format!("http-referrer={}", referer));
+        }
+        options
+    }
+}
+
+/// Длительность для `#EXTINF` и PLS (номинальная, см. [`PlaylistEntry::duration`]): -1, если неизвестна
+fn m3u_duration(entry: &PlaylistEntry) -> i64 {
+    entry.duration.map(i64::from).unwrap_or(-1)
+}
+
+/// Значение EDL в виде `%длина%текст`, чтобы запятые и пробелы не ломали разбор
+fn edl_value(value: &str) -> String {
+    format!("%{}%{}", value.len(), value)
+}
+
+/// Перевод строки внутри значения начал бы новую запись M3U8 или PLS
+fn single_line(value: &str) -> String {
+    value.replace(['\r', '\n'], " ")
+}
+
+fn xml_escape(value: &str) -> String {
+    value.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use anyhow::Result;
+use parking_lot::Mutex;
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use tokio::time::Instant;
+use url::Url;
+
+/// Политика HTTP-запросов к Kodik
+#[derive(Debug, Clone)]
+pub struct HttpPolicy {
+    /// Таймаут всего запроса
+    pub timeout: Duration,
+    pub connect_timeout: Duration,
+    /// Число повторов GET-запроса после первой попытки
+    pub max_retries: u32,
+    /// Задержка перед первым повтором, дальше удваивается
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Запросов в секунду на один хост
+    pub requests_per_second: f64,
+    /// Сколько запросов к хосту можно отправить подряд без ожидания
+    pub burst: u32,
+}
+
+impl Default for HttpPolicy {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(15),
+            connect_timeout: Duration::from_secs(5),
+            max_retries: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(8),
+            requests_per_second: 5.0,
+            burst: 10,
+        }
+    }
+}
+
+impl HttpPolicy {
+    /// Настройки из окружения: `KODIK_HTTP_TIMEOUT_SECS`, `KODIK_HTTP_RETRIES`
+    /// и `KODIK_HTTP_RPS` (запросов в секунду на хост); остальное — по умолчанию
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+            std::env::var(name).ok().and_then(|v| v.trim().parse().ok())
+        }
+
+        let mut policy = Self::default();
+        if let Some(secs) = var::<u64>("KODIK_HTTP_TIMEOUT_SECS") {
+            policy.timeout = Duration::from_secs(secs);
+        }
+        if let Some(retries) = var::<u32>("KODIK_HTTP_RETRIES") {
+            policy.max_retries = retries;
+        }
+        if let Some(rps) = var::<f64>("KODIK_HTTP_RPS").filter(|rps| rps.is_finite() && *rps > 0.0) {
+            policy.requests_per_second = rps;
+        }
+        policy
+    }
+
+    /// Задержка перед повтором `attempt` (начиная с 1)
+    fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+}
+
+/// Корзина токенов для одного хоста
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// HTTP-клиент Kodik с таймаутами, повторами GET и ограничением частоты по хостам
+#[derive(Debug)]
+pub struct KodikHttp {
+    client: Client,
+    policy: HttpPolicy,
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+}
+
+impl Default for KodikHttp {
+    fn default() -> Self {
+        Self::new(HttpPolicy::default())
+    }
+}
+
+impl KodikHttp {
+    pub fn new(policy: HttpPolicy) -> Self {
+        let client = Client::builder()
+            .timeout(policy.timeout)
+            .connect_timeout(policy.connect_timeout)
+            .build()
+            .unwrap_or_else(|_| Client::new());
+
+        Self {
+            client,
+            policy,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// GET с повторами; `stage` — название этапа для логов
+    pub async fn get(&self, stage: &str, url: &str) -> Result<Response> {
+        let mut attempt = 0;
+        loop {
+            self.acquire(url).await;
+            let error = match self.client.get(url).send().await {
+                Ok(response) if !Self::is_retryable_status(response.status()) => return Ok(response),
+                Ok(response) => anyhow::anyhow!("HTTP {}", response.status()),
+                Err(e) if e.is_timeout() || e.is_connect() || e.is_request() => e.into(),
+                Err(e) => return Err(e.into()),
+            };
+
+            attempt += 1;
+            if attempt > self.policy.max_retries {
+                log::error!("Kodik {}: giving up after {} attempts: {}", stage, attempt, error);
+                return Err(error);
+            }
+
+            let delay = self.policy.backoff(attempt);
+            log::warn!(
+                "Kodik {}: attempt {}/{} failed ({}), retrying in {:?}",
+                stage, attempt, self.policy.max_retries + 1, error, delay
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// GET и тело ответа как текст
+    pub async fn get_text(&self, stage: &str, url: &str) -> Result<String> {
+        Ok(self.get(stage, url).await?.text().await?)
+    }
+
+    /// Прочитать первые `limit` байт ответа (запрос с `Range`), остальное не загружается
+    pub async fn get_prefix(&self, stage: &str, url: &str, limit: u64) -> Result<u64> {
+        let range = format!("bytes=0-{}", limit.saturating_sub(1));
+        let mut response = self.send(url, self.client.get(url).header(reqwest::header::RANGE, range)).await?
+            .error_for_status()?;
+
+        let mut read = 0u64;
+        while read < limit {
+            match response.chunk().await? {
+                Some(chunk) => read += chunk.len() as u64,
+                None => break,
+            }
+        }
+        log::debug!("Kodik {}: read {} bytes of {}", stage, read, url);
+        Ok(read)
+    }
+
+    /// POST с формой: без повторов, только таймаут и ограничение частоты
+    pub async fn post_form<T: serde::Serialize + ?Sized>(&self, stage: &str, url: &str, form: &T) -> Result<Response> {
+        self.send(url, self.client.post(url).form(form)).await
+            .map_err(|e| {
+                log::warn!("Kodik {}: POST failed: {}", stage, e);
+                e
+            })
+    }
+
+    async fn send(&self, url: &str, request: RequestBuilder) -> Result<Response> {
+        self.acquire(url).await;
+        Ok(request.send().await?)
+    }
+
+    fn is_retryable_status(status: StatusCode) -> bool {
+        status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+    }
+
+    /// Дождаться свободного токена в корзине хоста
+    async fn acquire(&self, url: &str) {
+        let host = Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_string))
+            .unwrap_or_default();
+        let rate = self.policy.requests_per_second.max(0.1);
+        let burst = self.policy.burst.max(1) as f64;
+
+        loop {
+            let wait = {
+                let mut buckets = self.buckets.lock();
+                let bucket = buckets.entry(host.clone()).or_insert_with(|| TokenBucket {
+                    tokens: burst,
+                    updated: Instant::now(),
+                });
+
+                let now = Instant::now();
+                bucket.tokens = (bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * rate).min(burst);
+                bucket.updated = now;
+
+                if bucket.tokens >= 1.0 {
+                    bucket.tokens -= 1.0;
+                    return;
+                }
+                Duration::from_secs_f64((1.0 - bucket.tokens) / rate)
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(requests_per_second: f64, burst: u32) -> HttpPolicy {
+        HttpPolicy {
+            requests_per_second,
+            burst,
+            ..HttpPolicy::default()
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_max_delay() {
+        let policy = HttpPolicy {
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(3),
+            ..HttpPolicy::default()
+        };
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(500));
+        assert_eq!(policy.backoff(2), Duration::from_secs(1));
+        assert_eq!(policy.backoff(3), Duration::from_secs(2));
+        assert_eq!(policy.backoff(4), Duration::from_secs(3));
+        assert_eq!(policy.backoff(10), Duration::from_secs(3));
+    }
+
+    #[test]
+    fn backoff_saturates() {
+        let policy = HttpPolicy {
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::MAX,
+            ..HttpPolicy::default()
+        };
+
+        // 2^40 не помещается в u32, множитель упирается в u32::MAX
+        assert_eq!(policy.backoff(40), Duration::from_secs(u32::MAX as u64));
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(u32::MAX as u64));
+        assert_eq!(policy.backoff(0), Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn acquire_allows_burst_then_waits_for_rate() {
+        let http = KodikHttp::new(policy(2.0, 3));
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            http.acquire("https://kodik.info/serial/1").await;
+        }
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        // Корзина пуста: следующий токен появляется через 1 / rate
+        http.acquire("https://kodik.info/serial/1").await;
+        assert_eq!(start.elapsed(), Duration::from_millis(500));
+        http.acquire("https://kodik.info/serial/1").await;
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn acquire_keeps_buckets_per_host() {
+        let http = KodikHttp::new(policy(1.0, 1));
+        let start = Instant::now();
+
+        http.acquire("https://kodik.info/serial/1").await;
+        http.acquire("https://cloud.kodik-storage.com/useruploads/1").await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        http.acquire("https://kodik.info/serial/2").await;
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn acquire_refills_while_idle() {
+        let http = KodikHttp::new(policy(1.0, 2));
+
+        http.acquire("https://kodik.info/").await;
+        http.acquire("https://kodik.info/").await;
+        tokio::time::sleep(Duration::from_secs(5)).await;
+
+        // За время простоя корзина наполнилась, но не больше burst
+        let start = Instant::now();
+        http.acquire("https://kodik.info/").await;
+        http.acquire("https://kodik.info/").await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+        http.acquire("https://kodik.info/").await;
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+    }
+}
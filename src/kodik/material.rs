@@ -0,0 +1,122 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use super::api::TranslationKind;
+
+/// Метаданные тайтла (`material_data` в ответах `search` и `list`)
+///
+/// Все поля необязательны: состав `material_data` зависит от типа материала
+/// и от того, какие базы Kodik смог сопоставить. Списки, пришедшие как `null`, считаются пустыми.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MaterialData {
+    pub title: Option<String>,
+    pub anime_title: Option<String>,
+    pub title_en: Option<String>,
+    #[serde(deserialize_with = "null_as_empty")]
+    pub other_titles: Vec<String>,
+    #[serde(deserialize_with = "null_as_empty")]
+    pub other_titles_en: Vec<String>,
+    #[serde(deserialize_with = "null_as_empty")]
+    pub other_titles_jp: Vec<String>,
+    pub anime_kind: Option<String>,
+    pub anime_status: Option<String>,
+    pub all_status: Option<String>,
+    pub year: Option<i32>,
+    pub tagline: Option<String>,
+    pub description: Option<String>,
+    pub anime_description: Option<String>,
+    pub poster_url: Option<String>,
+    pub anime_poster_url: Option<String>,
+    #[serde(deserialize_with = "null_as_empty")]
+    pub screenshots: Vec<String>,
+    /// Длительность серии в минутах
+    pub duration: Option<i32>,
+    #[serde(deserialize_with = "null_as_empty")]
+    pub countries: Vec<String>,
+    #[serde(deserialize_with = "null_as_empty")]
+    pub genres: Vec<String>,
+    #[serde(deserialize_with = "null_as_empty")]
+    pub anime_genres: Vec<String>,
+    #[serde(deserialize_with = "null_as_empty")]
+    pub all_genres: Vec<String>,
+    #[serde(deserialize_with = "null_as_empty")]
+    pub anime_studios: Vec<String>,
+    pub kinopoisk_rating: Option<f32>,
+    pub kinopoisk_votes: Option<i32>,
+    pub imdb_rating: Option<f32>,
+    pub imdb_votes: Option<i32>,
+    pub shikimori_rating: Option<f32>,
+    pub shikimori_votes: Option<i32>,
+    pub premiere_world: Option<String>,
+    pub aired_at: Option<String>,
+    pub released_at: Option<String>,
+    pub next_episode_at: Option<String>,
+    pub rating_mpaa: Option<String>,
+    pub minimal_age: Option<i32>,
+    pub episodes_total: Option<i32>,
+    pub episodes_aired: Option<i32>,
+}
+
+/// `null` вместо списка — пустой список
+fn null_as_empty<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Ok(Option::<Vec<T>>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+impl MaterialData {
+    /// Постер: аниме-постер Shikimori, затем общий
+    pub fn poster(&self) -> Option<&str> {
+        self.anime_poster_url.as_deref().or(self.poster_url.as_deref())
+    }
+
+    /// Описание: аниме-описание Shikimori, затем общее
+    pub fn description(&self) -> Option<&str> {
+        self.anime_description.as_deref().or(self.description.as_deref())
+    }
+
+    /// Жанры: аниме-жанры Shikimori, затем общие
+    pub fn genres(&self) -> &[String] {
+        if !self.anime_genres.is_empty() {
+            &self.anime_genres
+        } else if !self.genres.is_empty() {
+            &self.genres
+        } else {
+            &self.all_genres
+        }
+    }
+
+    /// Оценка: Shikimori, затем Кинопоиск и IMDb
+    pub fn score(&self) -> Option<f32> {
+        self.shikimori_rating
+            .or(self.kinopoisk_rating)
+            .or(self.imdb_rating)
+    }
+
+    /// Статус выхода (`ongoing`, `released`, `anons`)
+    pub fn status(&self) -> Option<&str> {
+        self.anime_status.as_deref().or(self.all_status.as_deref())
+    }
+}
+
+/// Перевод, к которому относится результат поиска
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ReleaseTranslation {
+    pub id: i64,
+    pub title: String,
+    #[serde(rename = "type")]
+    pub translation_type: TranslationKind,
+}
+
+/// Перевод, доступный для тайтла, с состоянием выхода серий
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AvailableTranslation {
+    pub id: i64,
+    pub title: String,
+    pub translation_type: TranslationKind,
+    pub episodes_count: Option<i32>,
+    pub last_season: Option<i32>,
+    pub last_episode: Option<i32>,
+    pub updated_at: Option<String>,
+}
@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+use url::form_urlencoded::byte_serialize;
+use super::api::KodikError;
+
+/// Идентификатор тайтла во внешней базе
+///
+/// Сериализуется как `{"type": "shikimori", "id": "123"}`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", content = "id", rename_all = "lowercase")]
+pub enum MediaId {
+    Shikimori(String),
+    Kinopoisk(String),
+    Imdb(String),
+    /// Ссылка на страницу World Art
+    WorldArt(String),
+}
+
+impl MediaId {
+    /// Создать идентификатор по строковому типу ("shikimori", "kinopoisk", "imdb", "worldart")
+    pub fn new(id_type: &str, id: &str) -> Result<Self, KodikError> {
+        match id_type {
+            "shikimori" => Ok(MediaId::Shikimori(id.to_string())),
+            "kinopoisk" => Ok(MediaId::Kinopoisk(id.to_string())),
+            "imdb" => Ok(MediaId::Imdb(id.to_string())),
+            "worldart" => Ok(MediaId::WorldArt(id.to_string())),
+            _ => Err(KodikError::InvalidIdType(format!(
+                "Поддерживаются только id shikimori, kinopoisk, imdb, worldart. Получено: {}",
+                id_type
+            ))),
+        }
+    }
+
+    /// Значение идентификатора
+    pub fn value(&self) -> &str {
+        match self {
+            MediaId::Shikimori(id)
+            | MediaId::Kinopoisk(id)
+            | MediaId::Imdb(id)
+            | MediaId::WorldArt(id) => id,
+        }
+    }
+
+    /// Строковый тип идентификатора
+    pub fn kind(&self) -> &'static str {
+        match self {
+            MediaId::Shikimori(_) => "shikimori",
+            MediaId::Kinopoisk(_) => "kinopoisk",
+            MediaId::Imdb(_) => "imdb",
+            MediaId::WorldArt(_) => "worldart",
+        }
+    }
+
+    /// Имя параметра для `get-player` и `find-player`
+    pub fn player_param(&self) -> &'static str {
+        match self {
+            MediaId::Shikimori(_) => "shikimoriID",
+            MediaId::Kinopoisk(_) => "kinopoiskID",
+            MediaId::Imdb(_) => "imdbID",
+            MediaId::WorldArt(_) => "worldartLink",
+        }
+    }
+
+    /// Имя параметра для `search` и `list`
+    pub fn search_param(&self) -> &'static str {
+        match self {
+            MediaId::Shikimori(_) => "shikimori_id",
+            MediaId::Kinopoisk(_) => "kinopoisk_id",
+            MediaId::Imdb(_) => "imdb_id",
+            MediaId::WorldArt(_) => "worldart_link",
+        }
+    }
+
+    /// Значение, экранированное для подстановки в query string
+    pub fn encoded_value(&self) -> String {
+        byte_serialize(self.value().as_bytes()).collect()
+    }
+}
+
+impl std::fmt::Display for MediaId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.kind(), self.value())
+    }
+}
@@ -1,18 +1,38 @@
 mod api;
+mod cache;
+mod export;
+mod http;
+mod material;
+mod media_id;
+mod playlist;
+pub mod parsing;
+mod prefetch;
+mod query;
+mod self_test;
 
 use anyhow::Result;
 use shaku::Component;
 use crate::di::interfaces::{IKodikSearch, IKodikInfo, IKodikPlayback, IKodik};
-pub use api::{KodikParser, MediaResult, InfoResponse, Translation};
-use std::sync::Arc;
-use tokio::runtime::Runtime;
+use crate::hls::MasterPlaylist;
+pub use api::{KodikParser, KodikError, BrowseMode, BrowsePage, MediaResult, InfoResponse, Translation, TranslationKind, Season, EpisodeInfo, StreamInfo, StreamQuality, TokenSource, TokenStatus};
+pub use cache::{CacheConfig, CacheKind, ResponseCache, SharedError};
+pub use export::PlaylistFormat;
+pub use http::HttpPolicy;
+pub use material::{AvailableTranslation, MaterialData, ReleaseTranslation};
+pub use media_id::MediaId;
+pub use playlist::{Playlist, PlaylistEntry, PlaylistHeaders, PlaylistProgress, PLAYLIST_CONCURRENCY};
+pub use prefetch::PrefetchConfig;
+pub use query::{SearchQuery, AnimeKind, SearchSort, SortOrder};
+pub use self_test::{SelfTestReport, StageReport, DEFAULT_SELF_TEST_ID};
 use std::future::Future;
 use std::pin::Pin;
+use futures::StreamExt;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio_util::sync::CancellationToken;
 
 pub struct KodikClient {
     api_key: String,
     parser: KodikParser,
-    runtime: Arc<Runtime>,
 }
 
 impl<M: shaku::Module> Component<M> for KodikClient {
@@ -20,84 +40,176 @@ impl<M: shaku::Module> Component<M> for KodikClient {
     type Parameters = String;
 
     fn build(_context: &mut shaku::ModuleBuildContext<M>, api_key: Self::Parameters) -> Box<Self::Interface> {
-        let runtime = Arc::new(Runtime::new().unwrap());
-        let rt = runtime.clone();
-
-        let parser = rt.block_on(async {
-            KodikParser::new(Some(api_key.clone()), false)
-                .await
-                .expect("Failed to initialize KodikParser")
-        });
+        // KODIK_API_KEY может содержать несколько токенов через запятую — они пробуются по порядку,
+        // а при отказе всех токен будет получен автоматически
+        let tokens = api_key.split(',').map(str::to_string).collect();
+        let parser = KodikParser::with_tokens(tokens, false)
+            .with_http_policy(HttpPolicy::from_env())
+            .with_cache(CacheConfig::from_env())
+            .with_prefetch(PrefetchConfig::from_env());
 
         Box::new(KodikClient {
             api_key,
             parser,
-            runtime,
         })
     }
 }
 
-impl IKodik for KodikClient {}
+impl IKodik for KodikClient {
+    fn token_status<'a>(&'a self) -> Pin<Box<dyn Future<Output = Option<TokenStatus>> + Send + 'a>> {
+        Box::pin(async move {
+            self.parser.token_status().await
+        })
+    }
+
+    fn self_test<'a>(&'a self, id: &'a MediaId) -> Pin<Box<dyn Future<Output = SelfTestReport> + Send + 'a>> {
+        Box::pin(async move {
+            self.parser.self_test(id).await
+        })
+    }
+}
 
 impl IKodikSearch for KodikClient {
-    fn search_anime<'a>(&'a self, query: &'a str) -> Pin<Box<dyn Future<Output = Result<Vec<MediaResult>>> + Send + 'a>> {
+    fn search_anime<'a>(&'a self, query: &'a SearchQuery) -> Pin<Box<dyn Future<Output = Result<Vec<MediaResult>>> + Send + 'a>> {
         Box::pin(async move {
-            self.parser.search(query, Some(10), true, None, false, true).await
+            let query = SearchQuery {
+                limit: query.limit.or(Some(10)),
+                ..query.clone()
+            };
+            self.parser.search(&query, true, None, false, true).await
+        })
+    }
+
+    fn browse_anime<'a>(&'a self, mode: BrowseMode, cursor: Option<&'a str>) -> Pin<Box<dyn Future<Output = Result<BrowsePage>> + Send + 'a>> {
+        Box::pin(async move {
+            self.parser.browse(mode, 24, cursor.map(str::to_string)).await
         })
     }
 }
 
 impl IKodikInfo for KodikClient {
-    fn get_anime_info<'a>(&'a self, shikimori_id: &'a str) -> Pin<Box<dyn Future<Output = Result<InfoResponse>> + Send + 'a>> {
+    fn get_anime_info<'a>(&'a self, id: &'a MediaId) -> Pin<Box<dyn Future<Output = Result<InfoResponse>> + Send + 'a>> {
         Box::pin(async move {
-            self.parser.get_info(shikimori_id, "shikimori").await
+            self.parser.get_info(id).await
         })
     }
 
-    fn get_translations<'a>(&'a self, shikimori_id: &'a str) -> Pin<Box<dyn Future<Output = Result<Vec<Translation>>> + Send + 'a>> {
+    fn get_translations<'a>(&'a self, id: &'a MediaId) -> Pin<Box<dyn Future<Output = Result<Vec<Translation>>> + Send + 'a>> {
         Box::pin(async move {
-            let info = self.get_anime_info(shikimori_id).await?;
+            let info = self.get_anime_info(id).await?;
             Ok(info.translations)
         })
     }
 
-    fn get_series_count<'a>(&'a self, shikimori_id: &'a str) -> Pin<Box<dyn Future<Output = Result<i32>> + Send + 'a>> {
+    fn get_series_count<'a>(&'a self, id: &'a MediaId) -> Pin<Box<dyn Future<Output = Result<i32>> + Send + 'a>> {
         Box::pin(async move {
-            let info = self.get_anime_info(shikimori_id).await?;
+            let info = self.get_anime_info(id).await?;
             Ok(info.series_count)
         })
     }
+
+    fn get_available_translations<'a>(&'a self, id: &'a MediaId) -> Pin<Box<dyn Future<Output = Result<Vec<AvailableTranslation>>> + Send + 'a>> {
+        Box::pin(async move {
+            self.parser.available_translations(id).await
+        })
+    }
 }
 
 impl IKodikPlayback for KodikClient {
-    fn get_episode_link<'a>(&'a self, shikimori_id: &'a str, episode: i32, translation_id: &'a str) -> Pin<Box<dyn Future<Output = Result<(String, i32)>> + Send + 'a>> {
+    fn get_episode_link<'a>(&'a self, id: &'a MediaId, season: i32, episode: i32, translation_id: &'a str) -> Pin<Box<dyn Future<Output = Result<StreamInfo>> + Send + 'a>> {
+        Box::pin(async move {
+            self.parser.get_link(id, season, episode, translation_id).await
+        })
+    }
+
+    fn refresh_episode_link<'a>(&'a self, id: &'a MediaId, season: i32, episode: i32, translation_id: &'a str) -> Pin<Box<dyn Future<Output = Result<StreamInfo>> + Send + 'a>> {
+        Box::pin(async move {
+            self.parser.invalidate_link(id, season, episode, translation_id);
+            self.get_episode_link(id, season, episode, translation_id).await
+        })
+    }
+
+    fn prefetch_episode<'a>(&'a self, id: &'a MediaId, season: i32, episode: i32, translation_id: &'a str, quality: Option<i32>, refresh: bool) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>> {
+        Box::pin(async move {
+            self.parser.prefetch(id, season, episode, translation_id, quality, refresh).await
+        })
+    }
+
+    fn get_hls_variants<'a>(&'a self, hls_url: &'a str) -> Pin<Box<dyn Future<Output = Result<MasterPlaylist>> + Send + 'a>> {
         Box::pin(async move {
-            self.parser.get_link(shikimori_id, "shikimori", episode, translation_id).await
+            self.parser.get_hls_variants(hls_url).await
         })
     }
 
-    fn create_playlist<'a>(&'a self, _title: &'a str, shikimori_id: &'a str, translation_id: &'a str) -> Pin<Box<dyn Future<Output = Result<String>> + Send + 'a>> {
+    fn create_playlist<'a>(
+        &'a self,
+        title: &'a str,
+        id: &'a MediaId,
+        translation_id: &'a str,
+        quality: Option<i32>,
+        progress: Option<UnboundedSender<PlaylistProgress>>,
+        cancel: CancellationToken,
+    ) -> Pin<Box<dyn Future<Output = Result<Playlist>> + Send + 'a>> {
         Box::pin(async move {
-            let info = self.get_anime_info(shikimori_id).await?;
-            let mut playlist = String::from("#EXTM3U\n");
-            let episode_count = if info.series_count > 0 { info.series_count } else { 1 };
-
-            for episode in 1..=episode_count {
-                let (download_link, _quality) = self.get_episode_link(
-                    shikimori_id,
-                    episode,
-                    translation_id
-                ).await?;
-
-                let download_link = format!("https://{}/720.mp4/", download_link);
-                playlist.push_str(&format!(
-                    "#EXTINF:-1,Episode {}\n{}\n",
-                    episode,
-                    download_link
-                ));
+            let info = self.get_anime_info(id).await?;
+
+            // Для фильма — единственная "серия" с номером 0
+            let episodes: Vec<(i32, i32)> = if info.seasons.is_empty() {
+                vec![(0, 0)]
+            } else {
+                info.seasons.iter()
+                    .flat_map(|season| season.episodes.iter().map(move |episode| (season.number, episode.number)))
+                    .collect()
+            };
+            let multi_season = info.seasons.len() > 1;
+            let total = episodes.len();
+
+            // Номинальная длительность серии (в минутах) из material_data — одна на все серии,
+            // длина каждой серии отдельно не запрашивается; без неё плейлист тоже собирается
+            let duration = self.parser.base_search_by_id(id, Some(1), true).await
+                .ok()
+                .and_then(|response| response.results.into_iter().next())
+                .and_then(|result| result.material_data)
+                .and_then(|material| material.duration)
+                .filter(|minutes| *minutes > 0)
+                .map(|minutes| minutes as u32 * 60);
+
+            // Серии разрешаются параллельно; ошибка одной серии не прерывает сборку
+            let mut resolved = futures::stream::iter(episodes)
+                .map(|(season, episode)| async move {
+                    let stream = self.get_episode_link(id, season, episode, translation_id).await;
+                    PlaylistEntry::resolve(season, episode, multi_season, stream, quality, duration)
+                })
+                .buffer_unordered(PLAYLIST_CONCURRENCY);
+
+            let mut entries = Vec::with_capacity(total);
+            loop {
+                tokio::select! {
+                    _ = cancel.cancelled() => {
+                        return Err(anyhow::anyhow!("Playlist generation cancelled"));
+                    }
+                    entry = resolved.next() => match entry {
+                        Some(entry) => {
+                            if let Some(progress) = &progress {
+                                let _ = progress.send(PlaylistProgress {
+                                    resolved: entries.len() + 1,
+                                    total,
+                                    entry: entry.clone(),
+                                });
+                            }
+                            entries.push(entry);
+                        },
+                        None => break,
+                    }
+                }
             }
 
-            Ok(playlist)
+            entries.sort_by_key(|entry| (entry.season, entry.episode));
+            Ok(Playlist {
+                title: title.to_string(),
+                entries,
+                headers: PlaylistHeaders::default(),
+            })
         })
     }
 }
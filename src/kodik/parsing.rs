@@ -0,0 +1,415 @@
+//! Разбор страниц плеера Kodik без сетевых запросов
+//!
+//! Все функции принимают HTML/JS/JSON в виде текста и возвращают типизированные
+//! структуры, поэтому их можно проверять на сохранённых страницах (`tests/fixtures/kodik`).
+
+use std::collections::HashMap;
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use regex::Regex;
+use scraper::{Html, Selector};
+use url::Url;
+use super::api::{EpisodeInfo, KodikError, MediaResult, StreamInfo, StreamQuality, Translation, TranslationKind};
+use super::media_id::MediaId;
+
+pub const SEASONS_SELECTOR: &str = "div.serial-seasons-box select option";
+pub const EPISODES_SELECTOR: &str = "div.serial-series-box select option";
+pub const SERIAL_TRANSLATIONS_SELECTOR: &str = "div.serial-translations-box select option";
+pub const MOVIE_TRANSLATIONS_SELECTOR: &str = "div.movie-translations-box select option";
+const VIDEO_TYPE_PATTERN: &str = r"\.type\s*=\s*'([^']+)'";
+const VIDEO_HASH_PATTERN: &str = r"\.hash\s*=\s*'([^']+)'";
+const VIDEO_ID_PATTERN: &str = r"\.id\s*=\s*'([^']+)'";
+const URL_PARAMS_PATTERN: &str = r#"urlParams\s*=\s*'([^']+)'"#;
+const PLAYER_SCRIPT_SELECTOR: &str = "script[src*='/app.']";
+const POST_LINK_PATTERN: &str = r#"\$\.ajax\(\{[^}]*?url:\s*atob\("([^"]+)"\)"#;
+
+/// Ошибка смены вёрстки без снимка страницы (снимок добавляет вызывающий код)
+fn layout_changed(stage: &str, selector: &str) -> KodikError {
+    KodikError::LayoutChanged {
+        stage: stage.to_string(),
+        selector: selector.to_string(),
+        snapshot: None,
+    }
+}
+
+/// Тип страницы плеера
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayerKind {
+    Serial,
+    Video,
+}
+
+impl PlayerKind {
+    /// Определить тип по ссылке на плеер (`//kodik.info/serial/...` или `//kodik.info/video/...`)
+    pub fn from_link(link: &str) -> Option<Self> {
+        if link.contains(".info/s") {
+            Some(PlayerKind::Serial)
+        } else if link.contains(".info/v") {
+            Some(PlayerKind::Video)
+        } else {
+            None
+        }
+    }
+
+    pub fn translations_selector(&self) -> &'static str {
+        match self {
+            PlayerKind::Serial => SERIAL_TRANSLATIONS_SELECTOR,
+            PlayerKind::Video => MOVIE_TRANSLATIONS_SELECTOR,
+        }
+    }
+}
+
+/// Данные страницы плеера: переводы, сезоны и серии выбранного сезона
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerPage {
+    pub translations: Vec<Translation>,
+    /// Перевод, открытый на странице; к нему относятся `episodes`
+    pub selected_translation: Option<String>,
+    pub seasons: Vec<i32>,
+    pub selected_season: Option<i32>,
+    pub episodes: Vec<EpisodeInfo>,
+}
+
+/// Идентификатор и хэш материала конкретного перевода
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaRef {
+    pub id: String,
+    pub hash: String,
+}
+
+/// Значения `videoInfo` из скрипта плеера
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VideoInfo {
+    pub video_type: String,
+    pub hash: String,
+    pub id: String,
+}
+
+impl VideoInfo {
+    pub fn media_ref(&self) -> MediaRef {
+        MediaRef {
+            id: self.id.clone(),
+            hash: self.hash.clone(),
+        }
+    }
+}
+
+fn selector(css: &str) -> Selector {
+    Selector::parse(css).unwrap()
+}
+
+fn parse_number(value: Option<&str>) -> Option<i32> {
+    value.and_then(|v| v.trim().parse::<i32>().ok())
+}
+
+/// Разобрать страницу плеера
+pub fn parse_player_page(html: &str, kind: PlayerKind) -> PlayerPage {
+    let document = Html::parse_document(html);
+    let (seasons, selected_season) = seasons_of(&document);
+    let translations = translations_of(&document, kind);
+
+    // Без отметки selected браузер показывает первый перевод
+    let selected_translation = document.select(&selector(kind.translations_selector()))
+        .find(|option| option.value().attr("selected").is_some())
+        .and_then(|option| option.value().attr("data-id"))
+        .map(str::to_string)
+        .or_else(|| translations.first().map(|t| t.id.clone()));
+
+    PlayerPage {
+        translations,
+        selected_translation,
+        seasons,
+        selected_season,
+        episodes: episodes_of(&document),
+    }
+}
+
+/// Получить серии выбранного сезона (страница `?season=N`)
+pub fn parse_episodes(html: &str) -> Vec<EpisodeInfo> {
+    episodes_of(&Html::parse_document(html))
+}
+
+fn translations_of(document: &Html, kind: PlayerKind) -> Vec<Translation> {
+    let translations: Vec<Translation> = document.select(&selector(kind.translations_selector()))
+        .filter_map(|option| {
+            let id = option.value().attr("data-id")?;
+            Some(Translation {
+                id: id.to_string(),
+                translation_type: TranslationKind::from_kodik(option.value().attr("data-translation-type")),
+                name: option.text().collect::<String>().trim().to_string(),
+            })
+        })
+        .collect();
+
+    if translations.is_empty() {
+        // Единственный перевод — блок выбора на странице не выводится
+        vec![Translation {
+            id: "0".to_string(),
+            translation_type: TranslationKind::Unknown,
+            name: "Неизвестно".to_string(),
+        }]
+    } else {
+        translations
+    }
+}
+
+fn seasons_of(document: &Html) -> (Vec<i32>, Option<i32>) {
+    let mut seasons = Vec::new();
+    let mut selected = None;
+
+    for option in document.select(&selector(SEASONS_SELECTOR)) {
+        if let Some(number) = parse_number(option.value().attr("value")) {
+            if option.value().attr("selected").is_some() {
+                selected = Some(number);
+            }
+            seasons.push(number);
+        }
+    }
+
+    (seasons, selected)
+}
+
+fn episodes_of(document: &Html) -> Vec<EpisodeInfo> {
+    document.select(&selector(EPISODES_SELECTOR))
+        .filter_map(|option| {
+            Some(EpisodeInfo {
+                number: parse_number(option.value().attr("value"))?,
+                media_id: option.value().attr("data-id").map(str::to_string),
+                hash: option.value().attr("data-hash").map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+/// Найти id и хэш материала для перевода на странице плеера
+pub fn find_translation(html: &str, kind: PlayerKind, translation_id: &str) -> Result<MediaRef, KodikError> {
+    let document = Html::parse_document(html);
+    let options: Vec<_> = document.select(&selector(kind.translations_selector())).collect();
+    if options.is_empty() {
+        return Err(layout_changed("translations", kind.translations_selector()));
+    }
+
+    let option = options.into_iter()
+        .find(|option| option.value().attr("data-id") == Some(translation_id))
+        .ok_or_else(|| KodikError::NoResults(format!("Перевод с id \"{}\" не найден.", translation_id)))?;
+
+    let hash = option.value().attr("data-media-hash")
+        .ok_or_else(|| layout_changed("translations", "option[data-media-hash]"))?;
+    let id = option.value().attr("data-media-id")
+        .ok_or_else(|| layout_changed("translations", "option[data-media-id]"))?;
+
+    Ok(MediaRef {
+        id: id.to_string(),
+        hash: hash.to_string(),
+    })
+}
+
+/// Найти `videoInfo.type/hash/id` среди встроенных скриптов страницы
+pub fn parse_video_info(html: &str) -> Result<VideoInfo, KodikError> {
+    let type_regex = Regex::new(VIDEO_TYPE_PATTERN).unwrap();
+    let hash_regex = Regex::new(VIDEO_HASH_PATTERN).unwrap();
+    let id_regex = Regex::new(VIDEO_ID_PATTERN).unwrap();
+
+    let document = Html::parse_document(html);
+    document.select(&selector("script"))
+        .map(|script| script.text().collect::<String>())
+        .find_map(|text| {
+            let capture = |regex: &Regex| regex.captures(&text)
+                .and_then(|c| c.get(1))
+                .map(|m| m.as_str().to_string());
+            Some(VideoInfo {
+                video_type: capture(&type_regex)?,
+                hash: capture(&hash_regex)?,
+                id: capture(&id_regex)?,
+            })
+        })
+        .ok_or_else(|| layout_changed("video_info", "script: .type/.hash/.id = '...'"))
+}
+
+/// Извлечь `urlParams` (параметры подписи запроса ссылок)
+pub fn parse_url_params(html: &str) -> Result<HashMap<String, String>, KodikError> {
+    let regex = Regex::new(URL_PARAMS_PATTERN).unwrap();
+    let json = regex.captures(html)
+        .and_then(|c| c.get(1))
+        .ok_or_else(|| layout_changed("url_params", URL_PARAMS_PATTERN))?
+        .as_str();
+
+    let value: serde_json::Value = serde_json::from_str(json)
+        .map_err(|e| KodikError::ParserError(format!("Некорректные urlParams: {}", e)))?;
+
+    let map = value.as_object()
+        .ok_or_else(|| KodikError::ParserError("urlParams не является объектом".into()))?;
+
+    Ok(map.iter()
+        .map(|(key, value)| {
+            let value = match value {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            (key.clone(), value)
+        })
+        .collect())
+}
+
+/// Найти путь к скрипту плеера (`/assets/js/app.serial.*.js`, `app.player_single.*.js` и т.п.)
+pub fn parse_player_script_url(html: &str) -> Result<String, KodikError> {
+    let document = Html::parse_document(html);
+    let found = document.select(&selector(PLAYER_SCRIPT_SELECTOR))
+        .find_map(|script| script.value().attr("src"))
+        .map(str::to_string);
+    found.ok_or_else(|| layout_changed("player_script", PLAYER_SCRIPT_SELECTOR))
+}
+
+/// Извлечь путь POST-запроса ссылок из скрипта плеера (`$.ajax({type:"POST",url:atob("...")`)
+pub fn parse_post_link(script: &str) -> Result<String, KodikError> {
+    let regex = Regex::new(POST_LINK_PATTERN).unwrap();
+    let encoded = regex.captures(script)
+        .and_then(|c| c.get(1))
+        .ok_or_else(|| layout_changed("post_link", POST_LINK_PATTERN))?
+        .as_str();
+
+    let decoded = BASE64.decode(encoded.as_bytes())
+        .map_err(|e| KodikError::ParserError(format!("Некорректный адрес запроса: {}", e)))?;
+    String::from_utf8(decoded)
+        .map_err(|e| KodikError::ParserError(format!("Некорректный адрес запроса: {}", e)))
+}
+
+/// Разобрать ответ POST-запроса ссылок во все доступные качества
+pub fn parse_stream_links(response: &serde_json::Value) -> Result<StreamInfo, KodikError> {
+    let links = response["links"].as_object()
+        .ok_or_else(|| layout_changed("stream_links", "links"))?;
+
+    let mut qualities = Vec::new();
+    let mut expires_hint = None;
+    for (quality, sources) in links {
+        let quality = match quality.parse::<i32>() {
+            Ok(q) => q,
+            Err(_) => continue,
+        };
+        let src = match sources[0]["src"].as_str() {
+            Some(src) => src,
+            None => continue,
+        };
+        let decoded = decode_src(src)?;
+        if expires_hint.is_none() {
+            expires_hint = extract_expires_hint(&decoded);
+        }
+        qualities.push(build_stream_quality(quality, &decoded));
+    }
+
+    if qualities.is_empty() {
+        return Err(KodikError::ParserError("Не удалось найти ни одной ссылки на видео".into()));
+    }
+    qualities.sort_by_key(|q| q.quality);
+
+    Ok(StreamInfo {
+        qualities,
+        expires_hint,
+    })
+}
+
+/// ROT13 для латиницы, остальные символы без изменений
+fn rot13(string: &str) -> String {
+    string.chars()
+        .map(|c| match c {
+            'a'..='z' => (((c as u8 - b'a') + 13) % 26 + b'a') as char,
+            'A'..='Z' => (((c as u8 - b'A') + 13) % 26 + b'A') as char,
+            _ => c,
+        })
+        .collect()
+}
+
+/// Раскодировать `src` из ответа плеера (ROT13 + base64) в ссылку вида `//host/.../720.mp4:hls:manifest.m3u8`
+pub fn decode_src(src: &str) -> Result<String, KodikError> {
+    let mut url = rot13(src);
+    // Kodik отдаёт base64 без выравнивания
+    while url.len() % 4 != 0 {
+        url.push('=');
+    }
+    let decoded = BASE64.decode(url.as_bytes())
+        .map_err(|e| KodikError::ParserError(format!("Не удалось раскодировать ссылку: {}", e)))?;
+    Ok(String::from_utf8_lossy(&decoded).replace("https:", ""))
+}
+
+/// Собрать описание качества из раскодированной ссылки
+fn build_stream_quality(quality: i32, decoded: &str) -> StreamQuality {
+    let path = decoded.trim_start_matches('/');
+    let mp4_path = path.trim_end_matches(":hls:manifest.m3u8");
+    StreamQuality {
+        quality,
+        hls_url: format!("https://{}", path),
+        mp4_url: format!("https://{}/", mp4_path),
+    }
+}
+
+/// Сгруппировать результаты `search`/`list` по тайтлам
+///
+/// Kodik возвращает отдельный результат на каждый перевод, поэтому результаты
+/// группируются по `MediaId` (с сохранением порядка выдачи), а переводы
+/// собираются в `translations`. Результаты без идентификаторов не объединяются.
+pub fn group_results(results: &[MediaResult], only_anime: bool) -> Vec<MediaResult> {
+    let mut data: Vec<MediaResult> = Vec::new();
+    let mut positions: HashMap<MediaId, usize> = HashMap::new();
+
+    for result in results {
+        if only_anime {
+            if let Some(media_type) = &result.media_type {
+                if !["anime-serial", "anime"].contains(&media_type.as_str()) {
+                    continue;
+                }
+            }
+        }
+
+        let media_id = result.primary_id();
+        let translation = result.available_translation();
+
+        if let Some(&index) = media_id.as_ref().and_then(|id| positions.get(id)) {
+            let entry = &mut data[index];
+            if let Some(translation) = translation {
+                if !entry.translations.iter().any(|t| t.id == translation.id) {
+                    entry.translations.push(translation);
+                }
+            }
+            // Дата обновления тайтла — самая свежая среди его переводов
+            if result.updated_at > entry.updated_at {
+                entry.updated_at = result.updated_at.clone();
+            }
+            if entry.material_data.is_none() {
+                entry.material_data = result.material_data.clone();
+            }
+            continue;
+        }
+
+        let mut entry = result.clone();
+        entry.media_id = media_id.clone();
+        entry.translations = translation.into_iter().collect();
+        if let Some(id) = media_id {
+            positions.insert(id, data.len());
+        }
+        data.push(entry);
+    }
+
+    data
+}
+
+/// Курсор `next` из `next_page` ответа `list` (`https://kodikapi.com/list?...&next=...`)
+pub fn list_cursor(next_page: &str) -> Option<String> {
+    Url::parse(next_page).ok()?
+        .query_pairs()
+        .find(|(key, _)| key == "next")
+        .map(|(_, value)| value.into_owned())
+}
+
+/// MP4-файл за HLS-ссылкой Kodik (`.../720.mp4:hls:manifest.m3u8` → `.../720.mp4/`)
+pub fn mp4_url_of(hls_url: &str) -> Option<String> {
+    let mp4 = hls_url.strip_suffix(":hls:manifest.m3u8")?;
+    mp4.ends_with(".mp4").then(|| format!("{}/", mp4))
+}
+
+/// Извлечь метку срока действия (`...:2024011418/720.mp4`) из раскодированной ссылки
+fn extract_expires_hint(decoded: &str) -> Option<String> {
+    let regex = Regex::new(r":(\d+)/\d+\.mp4").unwrap();
+    regex.captures(decoded)
+        .and_then(|c| c.get(1))
+        .map(|m| m.as_str().to_string())
+}
@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+use super::api::StreamInfo;
+
+/// Сколько серий разрешается одновременно при сборке плейлиста
+pub const PLAYLIST_CONCURRENCY: usize = 4;
+
+/// User-Agent, с которым плеер запрашивает потоки (как у mpv)
+pub const PLAYLIST_USER_AGENT: &str = "Mozilla/5.0";
+
+/// Referer для CDN Kodik
+pub const PLAYLIST_REFERER: &str = "https://kodik.info/";
+
+/// HTTP-заголовки, которые плеер должен передавать при загрузке серий
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaylistHeaders {
+    pub user_agent: Option<String>,
+    pub referer: Option<String>,
+}
+
+impl Default for PlaylistHeaders {
+    fn default() -> Self {
+        Self {
+            user_agent: Some(PLAYLIST_USER_AGENT.to_string()),
+            referer: Some(PLAYLIST_REFERER.to_string()),
+        }
+    }
+}
+
+/// Серия плейлиста: ссылка или причина, по которой её не удалось получить
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaylistEntry {
+    pub season: i32,
+    pub episode: i32,
+    pub name: String,
+    pub url: Option<String>,
+    pub quality: Option<i32>,
+    /// Номинальная длительность серии в секундах из `material_data` тайтла,
+    /// одна на все серии; реальная длина конкретной серии может отличаться
+    #[serde(default)]
+    pub duration: Option<u32>,
+    pub error: Option<String>,
+}
+
+impl PlaylistEntry {
+    /// Собрать запись из результата получения ссылок с выбором качества
+    pub fn resolve(
+        season: i32,
+        episode: i32,
+        multi_season: bool,
+        stream: anyhow::Result<StreamInfo>,
+        quality: Option<i32>,
+        duration: Option<u32>,
+    ) -> Self {
+        let name = if multi_season {
+            format!("Season {} Episode {}", season, episode)
+        } else {
+            format!("Episode {}", episode)
+        };
+
+        let selected = stream.and_then(|stream| {
+            stream.select(quality)
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("No stream available"))
+        });
+
+        match selected {
+            Ok(selected) => Self {
+                season,
+                episode,
+                name,
+                url: Some(selected.mp4_url),
+                quality: Some(selected.quality),
+                duration,
+                error: None,
+            },
+            Err(e) => Self {
+                season,
+                episode,
+                name,
+                url: None,
+                quality: None,
+                duration,
+                error: Some(e.to_string()),
+            },
+        }
+    }
+
+    pub fn is_resolved(&self) -> bool {
+        self.url.is_some()
+    }
+}
+
+/// Ход сборки плейлиста: очередная готовая серия
+#[derive(Debug, Clone, Serialize)]
+pub struct PlaylistProgress {
+    pub resolved: usize,
+    pub total: usize,
+    pub entry: PlaylistEntry,
+}
+
+/// Плейлист тайтла в порядке сезонов и серий
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Playlist {
+    pub title: String,
+    pub entries: Vec<PlaylistEntry>,
+    #[serde(default)]
+    pub headers: PlaylistHeaders,
+}
+
+impl Playlist {
+    /// Серии, которые не удалось получить
+    pub fn failed(&self) -> impl Iterator<Item = &PlaylistEntry> {
+        self.entries.iter().filter(|e| !e.is_resolved())
+    }
+}
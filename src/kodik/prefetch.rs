@@ -0,0 +1,77 @@
+use anyhow::Result;
+use crate::hls::{self, HlsPlaylist, VariantPreference};
+use super::api::KodikParser;
+use super::media_id::MediaId;
+
+/// Настройки предзагрузки следующей серии
+#[derive(Debug, Clone, Default)]
+pub struct PrefetchConfig {
+    /// Сколько байт потока загрузить заранее, чтобы прогреть CDN; 0 — только ссылки
+    pub warm_bytes: u64,
+}
+
+impl PrefetchConfig {
+    /// Настройки из окружения: `KODIK_PREFETCH_WARM_MB` — сколько мегабайт потока загружать заранее
+    pub fn from_env() -> Self {
+        let warm_mb = std::env::var("KODIK_PREFETCH_WARM_MB")
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .unwrap_or(0);
+        Self {
+            warm_bytes: warm_mb * 1024 * 1024,
+        }
+    }
+}
+
+impl KodikParser {
+    /// Заранее получить ссылки на серию, чтобы они оказались в кэше к моменту запуска
+    ///
+    /// `refresh` — получить ссылки заново, даже если они уже в кэше (подписи ссылок
+    /// быстро истекают). Если в настройках задан `warm_bytes`, первые сегменты
+    /// HLS-варианта, который выберет плеер, загружаются и отбрасываются.
+    pub async fn prefetch(
+        &self,
+        id: &MediaId,
+        season: i32,
+        seria_num: i32,
+        translation_id: &str,
+        quality: Option<i32>,
+        refresh: bool,
+    ) -> Result<()> {
+        if refresh {
+            self.invalidate_link(id, season, seria_num, translation_id);
+        }
+        let stream = self.get_link(id, season, seria_num, translation_id).await?;
+
+        let warm_bytes = self.prefetch_config().warm_bytes;
+        if warm_bytes > 0 {
+            if let Some(selected) = stream.select(quality) {
+                self.warm_variant(&selected.hls_url, selected.quality, warm_bytes).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Загрузить первые `warm_bytes` сегментов варианта с высотой не больше `height`
+    async fn warm_variant(&self, hls_url: &str, height: i32, warm_bytes: u64) -> Result<()> {
+        let master = self.get_hls_variants(hls_url).await?;
+        let Some(variant) = master.select(VariantPreference::MaxHeight(height as u32)) else {
+            return Ok(());
+        };
+
+        let playlist = self.fetch_page("prefetch", &variant.uri).await?;
+        let media = match hls::parse(&playlist, &variant.uri)? {
+            HlsPlaylist::Media(media) => media,
+            HlsPlaylist::Master(_) => return Err(anyhow::anyhow!("Variant {} is a master playlist", variant.uri)),
+        };
+
+        let mut left = warm_bytes;
+        for segment in &media.segments {
+            if left == 0 {
+                break;
+            }
+            left = left.saturating_sub(self.fetch_prefix("prefetch", &segment.uri, left).await?);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,168 @@
+use chrono::Datelike;
+use serde::{Deserialize, Serialize};
+use super::api::{KodikError, TranslationKind};
+
+/// Самый ранний год, который имеет смысл передавать в фильтр `year`
+pub const MIN_YEAR: i32 = 1900;
+
+/// Тип аниме (`anime_kind` в API Kodik)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AnimeKind {
+    Tv,
+    Movie,
+    Ova,
+    Ona,
+    Special,
+    Music,
+}
+
+impl AnimeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnimeKind::Tv => "tv",
+            AnimeKind::Movie => "movie",
+            AnimeKind::Ova => "ova",
+            AnimeKind::Ona => "ona",
+            AnimeKind::Special => "special",
+            AnimeKind::Music => "music",
+        }
+    }
+}
+
+/// Поле сортировки результатов поиска
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchSort {
+    Year,
+    CreatedAt,
+    UpdatedAt,
+    ShikimoriRating,
+    KinopoiskRating,
+    ImdbRating,
+}
+
+impl SearchSort {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchSort::Year => "year",
+            SearchSort::CreatedAt => "created_at",
+            SearchSort::UpdatedAt => "updated_at",
+            SearchSort::ShikimoriRating => "shikimori_rating",
+            SearchSort::KinopoiskRating => "kinopoisk_rating",
+            SearchSort::ImdbRating => "imdb_rating",
+        }
+    }
+}
+
+/// Направление сортировки
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortOrder {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "asc",
+            SortOrder::Desc => "desc",
+        }
+    }
+}
+
+/// Параметры поиска в базе Kodik
+///
+/// Десериализуется из payload IPC-запроса `search` (поле `query` — синоним `title`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SearchQuery {
+    #[serde(alias = "query")]
+    pub title: String,
+    pub limit: Option<i32>,
+    pub year_from: Option<i32>,
+    pub year_to: Option<i32>,
+    pub genres: Vec<String>,
+    pub kinds: Vec<AnimeKind>,
+    pub translation_type: Option<TranslationKind>,
+    /// Минимальный рейтинг Shikimori (0–10)
+    pub min_rating: Option<f32>,
+    pub sort: Option<SearchSort>,
+    pub order: Option<SortOrder>,
+}
+
+impl SearchQuery {
+    /// Поиск только по названию
+    pub fn title(title: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Значение параметра `year`: список годов через запятую
+    ///
+    /// Границы приводятся к `MIN_YEAR..=текущий год`; диапазон целиком за их пределами — ошибка.
+    fn years(&self) -> Result<Option<String>, KodikError> {
+        let current = chrono::Local::now().year();
+        let (from, to) = match (self.year_from, self.year_to) {
+            (None, None) => return Ok(None),
+            (Some(from), None) => (from, current),
+            (None, Some(to)) => (MIN_YEAR, to),
+            (Some(from), Some(to)) => (from.min(to), from.max(to)),
+        };
+
+        if from > current {
+            return Err(KodikError::InvalidQuery(format!("Год {} ещё не наступил", from)));
+        }
+        if to < MIN_YEAR {
+            return Err(KodikError::InvalidQuery(format!("Год {} раньше {}", to, MIN_YEAR)));
+        }
+
+        let years = (from.max(MIN_YEAR)..=to.min(current))
+            .map(|y| y.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        Ok(Some(years))
+    }
+
+    /// Дополнительные поля формы для `search` (кроме названия, лимита и токена)
+    pub fn filter_fields(&self) -> Result<Vec<(&'static str, String)>, KodikError> {
+        let mut fields = Vec::new();
+
+        if let Some(years) = self.years()? {
+            fields.push(("year", years));
+        }
+
+        if !self.genres.is_empty() {
+            fields.push(("anime_genres", self.genres.join(",")));
+        }
+
+        if !self.kinds.is_empty() {
+            let kinds = self.kinds.iter().map(|k| k.as_str()).collect::<Vec<_>>().join(",");
+            fields.push(("anime_kind", kinds));
+        }
+
+        match self.translation_type {
+            Some(TranslationKind::Voice) => fields.push(("translation_type", "voice".to_string())),
+            Some(TranslationKind::Subtitles) => fields.push(("translation_type", "subtitles".to_string())),
+            Some(TranslationKind::Unknown) | None => {}
+        }
+
+        if let Some(rating) = self.min_rating {
+            if !rating.is_finite() || !(0.0..=10.0).contains(&rating) {
+                return Err(KodikError::InvalidQuery(format!("Рейтинг {} вне диапазона 0-10", rating)));
+            }
+            fields.push(("shikimori_rating", format!("{}-10", rating)));
+        }
+
+        if let Some(sort) = self.sort {
+            fields.push(("sort", sort.as_str().to_string()));
+            fields.push(("order", self.order.unwrap_or_default().as_str().to_string()));
+        }
+
+        Ok(fields)
+    }
+}
@@ -0,0 +1,129 @@
+use serde::Serialize;
+use super::api::KodikParser;
+use super::media_id::MediaId;
+use super::parsing::{self, PlayerKind};
+
+/// Тайтл для проверки по умолчанию (Fullmetal Alchemist: Brotherhood)
+pub const DEFAULT_SELF_TEST_ID: &str = "5114";
+
+/// Результат одного этапа проверки
+#[derive(Debug, Clone, Serialize)]
+pub struct StageReport {
+    pub stage: &'static str,
+    pub ok: bool,
+    pub detail: String,
+}
+
+/// Отчёт проверки цепочки получения ссылок Kodik
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfTestReport {
+    pub id: MediaId,
+    pub stages: Vec<StageReport>,
+}
+
+impl SelfTestReport {
+    /// Все этапы пройдены
+    pub fn passed(&self) -> bool {
+        !self.stages.is_empty() && self.stages.iter().all(|s| s.ok)
+    }
+
+    /// Записать результат этапа; при ошибке возвращает `None`, и проверка останавливается
+    fn record<T, E: std::fmt::Display>(
+        &mut self,
+        stage: &'static str,
+        result: Result<T, E>,
+        detail: impl FnOnce(&T) -> String,
+    ) -> Option<T> {
+        match result {
+            Ok(value) => {
+                self.stages.push(StageReport { stage, ok: true, detail: detail(&value) });
+                Some(value)
+            },
+            Err(e) => {
+                self.stages.push(StageReport { stage, ok: false, detail: e.to_string() });
+                None
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for SelfTestReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Kodik self-test for {}", self.id)?;
+        for stage in &self.stages {
+            writeln!(f, "  [{}] {}: {}", if stage.ok { " OK " } else { "FAIL" }, stage.stage, stage.detail)?;
+        }
+        write!(f, "{}", if self.passed() { "All stages passed" } else { "Self-test failed" })
+    }
+}
+
+impl KodikParser {
+    /// Пройти всю цепочку (токен, get-player, iframe, страница серии, скрипт плеера, POST)
+    /// для известного тайтла и отчитаться по каждому этапу
+    pub async fn self_test(&self, id: &MediaId) -> SelfTestReport {
+        let mut report = SelfTestReport {
+            id: id.clone(),
+            stages: Vec::new(),
+        };
+
+        let token = match self.active_token().await {
+            Ok(_) => self.token_status().await.ok_or_else(|| "no token available".to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        let Some(_) = report.record("token", token, |status| {
+            format!("{:?} token, {} candidate(s)", status.source, status.candidates)
+        }) else {
+            return report;
+        };
+
+        // Кэш не используется: проверяется реальная цепочка запросов
+        let Some(link) = report.record("link_to_info", self.request_player_link(id, true).await, |link| link.clone()) else {
+            return report;
+        };
+
+        let Some(player_page) = report.record("iframe", self.fetch_page("iframe", &link).await, |page| format!("{} bytes", page.len())) else {
+            return report;
+        };
+
+        // Первый перевод и первая серия выбранного сезона; для фильма серия 0
+        let kind = PlayerKind::from_link(&link).unwrap_or(PlayerKind::Video);
+        let page = parsing::parse_player_page(&player_page, kind);
+        let translation_id = page.translations.first().map(|t| t.id.clone()).unwrap_or_else(|| "0".to_string());
+        let season = page.selected_season.or(page.seasons.first().copied()).unwrap_or(1);
+        let episode = match kind {
+            PlayerKind::Serial => page.episodes.first().map(|e| e.number).unwrap_or(1),
+            PlayerKind::Video => 0,
+        };
+
+        let url = Self::episode_page_url(&player_page, season, episode, &translation_id);
+        let Some(url) = report.record("translation", url, |_| {
+            format!("translation {}, season {}, episode {} of {} translations", translation_id, season, episode, page.translations.len())
+        }) else {
+            return report;
+        };
+
+        let video_page = match self.fetch_page("video_page", &url).await {
+            Ok(video_page) => Self::parse_video_page(&video_page, Some(&player_page)),
+            Err(e) => Err(e),
+        };
+        let Some((video_info, url_params, script_url)) = report.record("video_page", video_page, |(info, _, _)| {
+            format!("type {}, id {}", info.video_type, info.id)
+        }) else {
+            return report;
+        };
+
+        let Some(post_link) = report.record("player_script", self.fetch_post_link(&script_url).await, |link| {
+            format!("{} -> {}", script_url, link)
+        }) else {
+            return report;
+        };
+
+        let streams = self.get_link_with_data(&video_info, &url_params, &post_link).await;
+        report.record("post", streams, |stream| {
+            let qualities: Vec<_> = stream.qualities.iter().map(|q| q.quality.to_string()).collect();
+            format!("qualities {}", qualities.join(", "))
+        });
+
+        report
+    }
+}
@@ -2,6 +2,7 @@ pub mod utils;
 pub mod di;
 pub mod shikimori;
 pub mod kodik;
+pub mod hls;
 pub mod mpv;
 pub mod storage;
 pub mod client;
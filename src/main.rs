@@ -11,6 +11,7 @@ use env_logger::Builder;
 use std::io::Write;
 use std::fs;
 use crate::storage::initialize_storage_path;
+use crate::kodik::{MediaId, DEFAULT_SELF_TEST_ID};
 
 mod di;
 mod gui;
@@ -19,6 +20,7 @@ mod utils;
 mod storage;
 mod client;
 mod kodik;
+mod hls;
 mod shikimori;
 
 // In debug mode, use Vite's dev server
@@ -59,6 +61,26 @@ fn setup_logger() -> Result<(), Box<dyn std::error::Error>> {
 }
 
 
+/// Run `kodik self-test [type:id]` and return the process exit code
+fn run_kodik_self_test(container: &Container, runtime: &Runtime, id: Option<&str>) -> i32 {
+    let id = match id.map(|id| id.split_once(':').unwrap_or(("shikimori", id))) {
+        Some((id_type, value)) => match MediaId::new(id_type, value) {
+            Ok(id) => id,
+            Err(e) => {
+                eprintln!("{}", e);
+                return 2;
+            }
+        },
+        None => MediaId::Shikimori(DEFAULT_SELF_TEST_ID.to_string()),
+    };
+
+    let report = runtime.block_on(container.kodik().self_test(&id));
+    println!("{}", report);
+    info!("{}", report);
+
+    if report.passed() { 0 } else { 1 }
+}
+
 fn main() -> wry::Result<()> {
     // Initialize logger first, before any other initialization
     if let Err(e) = setup_logger() {
@@ -67,6 +89,8 @@ fn main() -> wry::Result<()> {
         info!("Starting KamiView...");
     }
 
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
     // Load environment variables
     dotenv::dotenv().ok();
     
@@ -80,6 +104,11 @@ fn main() -> wry::Result<()> {
     
     // Initialize container and channels
     let container = Arc::new(Container::new());
+
+    // `kami_view kodik self-test [type:id]` checks the Kodik chain without starting the GUI
+    if args.len() >= 2 && args[0] == "kodik" && args[1] == "self-test" {
+        std::process::exit(run_kodik_self_test(&container, &runtime, args.get(2).map(String::as_str)));
+    }
     let (tx, rx) = tokio::sync::mpsc::channel::<String>(32);
     
     // Create window and event loop
@@ -0,0 +1,133 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use serde_json::{json, Value};
+use tokio::sync::broadcast;
+use log::{debug, warn};
+use crate::di::interfaces::MpvEvent;
+
+// How far before the end an EOF still counts as a broken stream, in seconds
+const PREMATURE_EOF_MARGIN: f64 = 5.0;
+// Minimum playback advance between progress events, in seconds
+const PROGRESS_INTERVAL: f64 = 5.0;
+
+// Observed property ids
+const TIME_POS_ID: u64 = 1;
+const DURATION_ID: u64 = 2;
+const EOF_REACHED_ID: u64 = 3;
+
+/// Spawn a thread that follows mpv's JSON IPC events for the current instance.
+///
+/// The listener uses its own connection to the socket and stops when mpv closes it.
+pub(crate) fn spawn_listener(socket_path: String, events: broadcast::Sender<MpvEvent>) {
+    std::thread::spawn(move || {
+        if let Err(e) = listen(&socket_path, &events) {
+            debug!("MPV event listener stopped: {}", e);
+        }
+    });
+}
+
+#[cfg(unix)]
+fn connect(socket_path: &str) -> io::Result<(Box<dyn Read + Send>, Box<dyn Write + Send>)> {
+    let stream = std::os::unix::net::UnixStream::connect(socket_path)?;
+    Ok((Box::new(stream.try_clone()?), Box::new(stream)))
+}
+
+#[cfg(windows)]
+fn connect(socket_path: &str) -> io::Result<(Box<dyn Read + Send>, Box<dyn Write + Send>)> {
+    let pipe = std::fs::OpenOptions::new().read(true).write(true).open(socket_path)?;
+    Ok((Box::new(pipe.try_clone()?), Box::new(pipe)))
+}
+
+fn listen(socket_path: &str, events: &broadcast::Sender<MpvEvent>) -> io::Result<()> {
+    let (reader, mut writer) = connect(socket_path)?;
+    for (id, name) in [(TIME_POS_ID, "time-pos"), (DURATION_ID, "duration"), (EOF_REACHED_ID, "eof-reached")] {
+        writeln!(writer, "{}", json!({ "command": ["observe_property", id, name] }))?;
+    }
+    writer.flush()?;
+
+    let mut tracker = PlaybackTracker::default();
+    for line in BufReader::new(reader).lines() {
+        let Ok(message) = serde_json::from_str::<Value>(&line?) else {
+            continue;
+        };
+
+        if let Some(event) = tracker.handle(&message) {
+            let failed = matches!(event, MpvEvent::StreamFailed { .. });
+            let _ = events.send(event);
+            // One failure report per instance, the reload starts a new one
+            if failed {
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Turns mpv's raw IPC messages into progress and stream failure events
+#[derive(Default)]
+pub struct PlaybackTracker {
+    position: Option<f64>,
+    duration: Option<f64>,
+    reported_position: Option<f64>,
+}
+
+impl PlaybackTracker {
+    pub fn handle(&mut self, message: &Value) -> Option<MpvEvent> {
+        match message.get("event").and_then(Value::as_str)? {
+            "property-change" => {
+                let data = message.get("data");
+                match message.get("id").and_then(Value::as_u64)? {
+                    TIME_POS_ID => {
+                        if let Some(position) = data.and_then(Value::as_f64) {
+                            self.position = Some(position);
+                            return self.progress(position);
+                        }
+                    },
+                    DURATION_ID => {
+                        if let Some(duration) = data.and_then(Value::as_f64) {
+                            self.duration = Some(duration);
+                        }
+                    },
+                    EOF_REACHED_ID if data.and_then(Value::as_bool) == Some(true) => {
+                        // With --keep-open a dropped connection looks like an early EOF
+                        if let (Some(position), Some(duration)) = (self.position, self.duration) {
+                            if position < duration - PREMATURE_EOF_MARGIN {
+                                return self.failure("stream ended early");
+                            }
+                        }
+                    },
+                    _ => {}
+                }
+                None
+            },
+            "end-file" if message.get("reason").and_then(Value::as_str) == Some("error") => {
+                let reason = message.get("file_error")
+                    .and_then(Value::as_str)
+                    .unwrap_or("loading failed");
+                self.failure(reason)
+            },
+            _ => None,
+        }
+    }
+
+    // Throttled to one event per PROGRESS_INTERVAL of playback, seeks included
+    fn progress(&mut self, position: f64) -> Option<MpvEvent> {
+        let duration = self.duration?;
+        if self.reported_position.is_some_and(|reported| (position - reported).abs() < PROGRESS_INTERVAL) {
+            return None;
+        }
+        self.reported_position = Some(position);
+        Some(MpvEvent::Progress { position, duration })
+    }
+
+    // Only streams that were actually playing are reported, so a reload that
+    // fails right away does not loop
+    fn failure(&self, reason: &str) -> Option<MpvEvent> {
+        let position = self.position?;
+        warn!("MPV stream failed at {:.1}s: {}", position, reason);
+        Some(MpvEvent::StreamFailed {
+            position,
+            reason: reason.to_string(),
+        })
+    }
+}
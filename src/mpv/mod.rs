@@ -1,9 +1,10 @@
 use serde_json::Value;
 use shaku::Component;
-use crate::di::interfaces::{IMpvClient, PlaybackInfo};
+use crate::di::interfaces::{IMpvClient, MpvEvent, PlaybackInfo};
 use std::sync::{Arc, Mutex};
 use std::process::{Command, Child};
 use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
 use tokio::sync::mpsc::{self, Sender, Receiver};
 use anyhow::Result;
 use mpv_socket::Property;
@@ -11,10 +12,15 @@ use std::path::Path;
 use std::fs;
 use log::{error, info, debug};
 
+pub mod events;
+
 // Message types for the MPV handler thread
 #[derive(Debug)]
 pub enum MpvMessage {
-    Play(String),
+    Play {
+        url: String,
+        start: Option<f64>,
+    },
     GetPlaybackInfo(tokio::sync::oneshot::Sender<Result<PlaybackInfo>>),
     Pause,
     Resume,
@@ -74,6 +80,8 @@ pub struct MpvClient {
     socket_path: String,
     #[shaku(default = Arc::new(Mutex::new(None::<Sender<MpvMessage>>)))]
     sender: Arc<Mutex<Option<Sender<MpvMessage>>>>,
+    #[shaku(default = broadcast::channel(16).0)]
+    events: broadcast::Sender<MpvEvent>,
 }
 
 impl MpvClient {
@@ -92,11 +100,12 @@ impl MpvClient {
 
             let (tx, rx) = mpsc::channel(32);
             let socket_path = self.socket_path.clone();
+            let events = self.events.clone();
 
             // Move rx into the spawned task
             tokio::task::spawn_blocking(move || {
                 let rt = tokio::runtime::Runtime::new().unwrap();
-                rt.block_on(Self::run_mpv_handler(socket_path, rx));
+                rt.block_on(Self::run_mpv_handler(socket_path, rx, events));
             });
 
             // Wait for MPV handler to initialize
@@ -107,20 +116,21 @@ impl MpvClient {
         Ok(sender_guard.as_ref().unwrap().clone())
     }
 
-    async fn run_mpv_handler(socket_path: String, mut rx: Receiver<MpvMessage>) {
+    async fn run_mpv_handler(socket_path: String, mut rx: Receiver<MpvMessage>, events: broadcast::Sender<MpvEvent>) {
         let mut current_state: Option<MpvState> = None;
 
         while let Some(msg) = rx.recv().await {
             match msg {
-                MpvMessage::Play(url) => {
+                MpvMessage::Play { url, start } => {
                     // Cleanup previous instance if exists
                     if let Some(state) = current_state.take() {
                         Self::cleanup_instance(state.instance).await;
                     }
 
-                    match Self::create_mpv_instance(&socket_path, &url).await {
+                    match Self::create_mpv_instance(&socket_path, &url, start).await {
                         Ok(instance) => {
                             current_state = Some(MpvState::new(instance));
+                            events::spawn_listener(socket_path.clone(), events.clone());
                         }
                         Err(e) => error!("Failed to start MPV: {}", e),
                     }
@@ -180,7 +190,7 @@ impl MpvClient {
         let _ = fs::remove_file(&socket_path);
     }
 
-    async fn create_mpv_instance(socket_path: &str, url: &str) -> Result<MpvInstance> {
+    async fn create_mpv_instance(socket_path: &str, url: &str, start: Option<f64>) -> Result<MpvInstance> {
         // Kill any existing MPV processes first
         #[cfg(target_os = "windows")]
         let _ = Command::new("taskkill")
@@ -198,7 +208,8 @@ impl MpvClient {
         debug!("Starting MPV with URL: {}", url);
         
         // Add mut to process
-        let mut process = Command::new("mpv")
+        let mut command = Command::new("mpv");
+        command
             .arg(format!("--input-ipc-server={}", socket_path))
             .arg("--force-window=yes")
             .arg("--keep-open=yes")
@@ -217,7 +228,13 @@ impl MpvClient {
             .arg("--input-vo-keyboard=yes")
             .arg("--osc=yes")
             .arg("--no-input-terminal")
-            .arg("--no-config")
+            .arg("--no-config");
+
+        if let Some(start) = start {
+            command.arg(format!("--start={:.3}", start));
+        }
+
+        let mut process = command
             .arg(url)
             .spawn()
             .map_err(|e| anyhow::anyhow!("Failed to start MPV: {}", e))?;
@@ -321,9 +338,13 @@ impl MpvClient {
 }
 
 impl IMpvClient for MpvClient {
-    fn play(&self, url: &str) -> Result<()> {
+    fn play(&self, url: &str, start: Option<f64>) -> Result<()> {
         let sender = futures::executor::block_on(self.ensure_mpv_handler())?;
-        futures::executor::block_on(sender.send(MpvMessage::Play(url.to_string())))
+        let message = MpvMessage::Play {
+            url: url.to_string(),
+            start,
+        };
+        futures::executor::block_on(sender.send(message))
             .map_err(|e| anyhow::anyhow!("Failed to send play command: {}", e))
     }
 
@@ -339,6 +360,10 @@ impl IMpvClient for MpvClient {
             Err(_) => Ok(PlaybackInfo::default())
         }
     }
+
+    fn subscribe_events(&self) -> broadcast::Receiver<MpvEvent> {
+        self.events.subscribe()
+    }
 }
 
 impl Drop for MpvClient {
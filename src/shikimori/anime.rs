@@ -0,0 +1,147 @@
+use anyhow::Result;
+use regex::Regex;
+use reqwest::Client;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::sync::OnceLock;
+use super::client::USER_AGENT;
+
+/// Poster variants; Shikimori returns them as site-relative paths
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnimeImage {
+    pub original: Option<String>,
+    pub preview: Option<String>,
+    pub x96: Option<String>,
+    pub x48: Option<String>,
+}
+
+impl AnimeImage {
+    /// Turn relative paths into absolute URLs on `origin` (e.g. `https://shikimori.one`)
+    pub fn absolute(self, origin: &str) -> Self {
+        let resolve = |path: Option<String>| path.map(|path| {
+            if path.starts_with("http://") || path.starts_with("https://") {
+                path
+            } else {
+                format!("{}{}", origin.trim_end_matches('/'), path)
+            }
+        });
+
+        Self {
+            original: resolve(self.original),
+            preview: resolve(self.preview),
+            x96: resolve(self.x96),
+            x48: resolve(self.x48),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Genre {
+    pub id: i64,
+    pub name: String,
+    pub russian: Option<String>,
+    pub kind: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Studio {
+    pub id: i64,
+    pub name: String,
+    pub filtered_name: Option<String>,
+    pub image: Option<String>,
+}
+
+/// Anime details from `GET /api/animes/:id`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnimeDetails {
+    pub id: i64,
+    pub name: String,
+    pub russian: Option<String>,
+    #[serde(default)]
+    pub english: Vec<Option<String>>,
+    #[serde(default)]
+    pub japanese: Vec<Option<String>>,
+    #[serde(default)]
+    pub synonyms: Vec<String>,
+    #[serde(default)]
+    pub image: AnimeImage,
+    pub url: Option<String>,
+    /// `tv`, `movie`, `ova`, `ona`, `special`...
+    pub kind: Option<String>,
+    /// The API sends the score as a string ("8.75")
+    #[serde(default, deserialize_with = "deserialize_score")]
+    pub score: Option<f32>,
+    /// `anons`, `ongoing` or `released`
+    pub status: Option<String>,
+    pub episodes: Option<i32>,
+    pub episodes_aired: Option<i32>,
+    pub aired_on: Option<String>,
+    pub released_on: Option<String>,
+    pub next_episode_at: Option<String>,
+    /// Age rating: `g`, `pg`, `pg_13`, `r`, `r_plus`, `rx`
+    pub rating: Option<String>,
+    /// Episode duration in minutes
+    pub duration: Option<i32>,
+    /// Description with Shikimori BBCode markup
+    pub description: Option<String>,
+    pub description_html: Option<String>,
+    pub franchise: Option<String>,
+    #[serde(default)]
+    pub genres: Vec<Genre>,
+    #[serde(default)]
+    pub studios: Vec<Studio>,
+}
+
+impl AnimeDetails {
+    /// Description without BBCode tags such as `[character=1]Edward[/character]`
+    pub fn plain_description(&self) -> Option<String> {
+        static TAGS: OnceLock<Regex> = OnceLock::new();
+        let tags = TAGS.get_or_init(|| Regex::new(r"\[/?[a-z_]+(=[^\]]*)?\]").unwrap());
+
+        self.description.as_deref()
+            .map(|description| tags.replace_all(description, "").trim().to_string())
+            .filter(|description| !description.is_empty())
+    }
+}
+
+fn deserialize_score<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<f32>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Score {
+        Number(f32),
+        Text(String),
+    }
+
+    Ok(match Option::<Score>::deserialize(deserializer)? {
+        Some(Score::Number(score)) => Some(score),
+        Some(Score::Text(score)) => score.trim().parse().ok(),
+        None => None,
+    }.filter(|score| *score > 0.0))
+}
+
+/// Site origin for a base API url: `https://shikimori.one/api` -> `https://shikimori.one`
+pub fn site_origin(base_url: &str) -> String {
+    match url::Url::parse(base_url) {
+        Ok(url) => url.origin().ascii_serialization(),
+        Err(_) => base_url.trim_end_matches("/api").to_string(),
+    }
+}
+
+/// Fetch `/animes/:id`; the endpoint is public and needs no token
+pub async fn fetch_anime(client: &Client, base_url: &str, id: i64) -> Result<AnimeDetails> {
+    log::info!("Fetching Shikimori anime {}", id);
+
+    let response = client
+        .get(format!("{}/animes/{}", base_url.trim_end_matches('/'), id))
+        .header("User-Agent", USER_AGENT)
+        .send()
+        .await?;
+
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(anyhow::anyhow!("Anime {} not found on Shikimori", id));
+    }
+
+    let mut anime = response.error_for_status()?.json::<AnimeDetails>().await?;
+    anime.image = anime.image.absolute(&site_origin(base_url));
+    Ok(anime)
+}
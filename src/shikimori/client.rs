@@ -1,15 +1,18 @@
 use anyhow::Result;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use tokio::sync::RwLock;
 use std::sync::Arc;
+use tokio::sync::broadcast;
+use crate::di::interfaces::AuthEvent;
+use super::loopback::{LoopbackListener, LOOPBACK_TIMEOUT};
 use super::storage::{Storage, AuthTokens};
+use super::tokens::TokenManager;
 
 
 const AUTH_URL: &str = "https://shikimori.one/oauth/authorize";
-const TOKEN_URL: &str = "https://shikimori.one/oauth/token";
+pub(crate) const TOKEN_URL: &str = "https://shikimori.one/oauth/token";
 const API_URL: &str = "https://shikimori.one/api";
-const USER_AGENT: &str = "mpv-integrade";
+pub(crate) const USER_AGENT: &str = "mpv-integrade";
 const REDIRECT_URI: &str = "urn:ietf:wg:oauth:2.0:oob";
 
 #[derive(Debug, Clone)]
@@ -55,7 +58,7 @@ pub struct UserInfo {
 #[derive(Debug)]
 pub struct ShikimoriOAuth {
     client: Arc<Client>,
-    tokens: Arc<RwLock<Option<TokenResponse>>>,
+    tokens: Arc<TokenManager>,
     storage: Storage
 }
 
@@ -63,45 +66,53 @@ impl ShikimoriOAuth {
     pub fn new() -> Result<Self> {
         log::info!("Initializing ShikimoriOAuth service");
         let storage = Storage::new()?;
-        
-        // Try to load saved tokens
-        let tokens = if let Some(auth_tokens) = storage.load_auth_tokens()? {
-            log::info!("Loaded saved token");
-            Some(TokenResponse {
-                access_token: auth_tokens.access_token,
-                refresh_token: auth_tokens.refresh_token,
-                expires_in: auth_tokens.expires_at.saturating_sub(
-                    std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs()
-                ),
-                token_type: "Bearer".to_string(),
-                scope: "user_rates".to_string(),
-                created_at: auth_tokens.expires_at - 3600, // Approximate creation time
-            })
-        } else {
-            None
-        };
+        let client = Arc::new(Client::new());
+        let tokens = TokenManager::new(client.clone(), storage.clone())?;
 
         Ok(Self {
-            client: Arc::new(Client::new()),
-            tokens: Arc::new(RwLock::new(tokens)),
+            client,
+            tokens: Arc::new(tokens),
             storage
         })
     }
 
     pub fn get_auth_url(&self) -> String {
+        self.auth_url(REDIRECT_URI, None)
+    }
+
+    fn auth_url(&self, redirect_uri: &str, state: Option<&str>) -> String {
         log::debug!("Generating auth URL");
-        format!(
-            "{}?client_id={}&redirect_uri={}&response_type=code&scope=user_rates",
-            AUTH_URL,
-            self.storage.shikimori_client_id(),
-            REDIRECT_URI
-        )
+        let mut url = url::Url::parse(AUTH_URL).expect("Invalid Shikimori auth URL");
+        url.query_pairs_mut()
+            .append_pair("client_id", self.storage.shikimori_client_id())
+            .append_pair("redirect_uri", redirect_uri)
+            .append_pair("response_type", "code")
+            .append_pair("scope", "user_rates");
+        if let Some(state) = state {
+            url.query_pairs_mut().append_pair("state", state);
+        }
+        url.into()
     }
 
     pub async fn exchange_code(&self, code: &str) -> Result<TokenResponse> {
+        self.exchange_code_for(code, REDIRECT_URI).await
+    }
+
+    /// Authorize through a redirect to a local listener on `port` instead of the
+    /// out-of-band code page; the browser is opened by this call
+    pub async fn login_loopback(&self, port: u16) -> Result<TokenResponse> {
+        let listener = LoopbackListener::bind(port)?;
+        let redirect_uri = listener.redirect_uri().to_string();
+
+        let url = self.auth_url(&redirect_uri, Some(listener.state()));
+        open::that(&url)?;
+
+        log::info!("Waiting for the Shikimori redirect on {}", redirect_uri);
+        let code = tokio::task::spawn_blocking(move || listener.wait_for_code(LOOPBACK_TIMEOUT)).await??;
+        self.exchange_code_for(&code, &redirect_uri).await
+    }
+
+    async fn exchange_code_for(&self, code: &str, redirect_uri: &str) -> Result<TokenResponse> {
         log::info!("Exchanging auth code for token");
         log::debug!("Auth code: {}", code);
 
@@ -113,7 +124,7 @@ impl ShikimoriOAuth {
                 ("client_id", self.storage.shikimori_client_id()),
                 ("client_secret", self.storage.shikimori_client_secret()),
                 ("code", code),
-                ("redirect_uri", REDIRECT_URI),
+                ("redirect_uri", redirect_uri),
             ])
             .send()
             .await?;
@@ -122,81 +133,63 @@ impl ShikimoriOAuth {
         log::info!("Successfully obtained token");
         log::debug!("Token expires in {} seconds", token.expires_in);
         
-        *self.tokens.write().await = Some(token.clone());
-        self.storage.save_auth_tokens(&AuthTokens::from(token.clone()))?;
+        self.tokens.set(&token).await?;
         Ok(token)
     }
 
-    pub async fn refresh_token(&self) -> Result<TokenResponse> {
-        log::info!("Refreshing token");
-        
-        let current_tokens = self.tokens.read().await;
-        let refresh_token = current_tokens.as_ref()
-            .map(|t| t.refresh_token.clone())
-            .ok_or_else(|| anyhow::anyhow!("No refresh token available"))?;
+    /// New access token after `rejected` got a 401; concurrent refreshes are merged
+    pub async fn refresh_token(&self, rejected: &str) -> Result<String> {
+        self.tokens.refresh_after(rejected).await
+    }
 
-        let response = self.client
-            .post(TOKEN_URL)
-            .header("User-Agent", USER_AGENT)
-            .form(&[
-                ("grant_type", "refresh_token"),
-                ("client_id", &self.storage.shikimori_client_id()),
-                ("client_secret", &self.storage.shikimori_client_secret()),
-                ("refresh_token", &refresh_token),
-            ])
-            .send()
-            .await?;
+    /// Access token for an API call, refreshed first when close to expiry
+    pub async fn access_token(&self) -> Result<String> {
+        self.tokens.access_token().await
+    }
 
-        let token = response.json::<TokenResponse>().await?;
-        log::info!("Successfully refreshed token");
-        log::debug!("New token expires in {} seconds", token.expires_in);
-        
-        *self.tokens.write().await = Some(token.clone());
-        self.storage.save_auth_tokens(&AuthTokens::from(token.clone()))?;
-        Ok(token)
+    pub async fn is_logged_in(&self) -> bool {
+        self.tokens.is_logged_in().await
+    }
+
+    /// Refresh tokens ahead of expiry in the background; runs until the app exits
+    pub async fn keep_tokens_fresh(&self) {
+        self.tokens.run().await
+    }
+
+    pub fn subscribe_auth_events(&self) -> broadcast::Receiver<AuthEvent> {
+        self.tokens.subscribe()
     }
 
     pub async fn get_user_info(&self) -> Result<UserInfo> {
         log::info!("Fetching user info");
-        
-        let tokens = self.tokens.read().await;
-        let access_token = tokens.as_ref()
-            .map(|t| t.access_token.clone())
-            .ok_or_else(|| anyhow::anyhow!("No access token available"))?;
 
-        let response = self.client
-            .get(format!("{}/users/whoami", API_URL))
-            .header("User-Agent", USER_AGENT)
-            .header("Authorization", format!("Bearer {}", access_token))
-            .send()
-            .await?;
+        let access_token = self.tokens.access_token().await?;
+        let mut response = self.whoami(&access_token).await?;
 
         if response.status() == reqwest::StatusCode::UNAUTHORIZED {
-            log::warn!("Access token expired, attempting to refresh");
-            drop(tokens);
-            let new_token = self.refresh_token().await?;
-            
+            log::warn!("Access token rejected, attempting to refresh");
+            let access_token = self.tokens.refresh_after(&access_token).await?;
+
             log::debug!("Retrying user info request with new token");
-            let response = self.client
-                .get(format!("{}/users/whoami", API_URL))
-                .header("User-Agent", USER_AGENT)
-                .header("Authorization", format!("Bearer {}", new_token.access_token))
-                .send()
-                .await?;
-
-            let user_info = response.json::<UserInfo>().await?;
-            log::info!("Successfully fetched user info for: {} (ID: {})", user_info.nickname, user_info.id);
-            Ok(user_info)
-        } else {
-            let user_info = response.json::<UserInfo>().await?;
-            log::info!("Successfully fetched user info for: {} (ID: {})", user_info.nickname, user_info.id);
-            Ok(user_info)
+            response = self.whoami(&access_token).await?;
         }
+
+        let user_info = response.error_for_status()?.json::<UserInfo>().await?;
+        log::info!("Successfully fetched user info for: {} (ID: {})", user_info.nickname, user_info.id);
+        Ok(user_info)
+    }
+
+    async fn whoami(&self, access_token: &str) -> Result<reqwest::Response> {
+        Ok(self.client
+            .get(format!("{}/users/whoami", API_URL))
+            .header("User-Agent", USER_AGENT)
+            .header("Authorization", format!("Bearer {}", access_token))
+            .send()
+            .await?)
     }
 
     pub async fn logout(&self) -> Result<()> {
-        *self.tokens.write().await = None;
-        self.storage.delete_auth_tokens()?;
+        self.tokens.clear().await?;
         log::info!("User logged out");
         Ok(())
     }
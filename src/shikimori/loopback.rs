@@ -0,0 +1,97 @@
+use anyhow::Result;
+use std::time::{Duration, Instant};
+use tiny_http::{Header, Response, Server};
+use url::Url;
+
+/// How long the listener waits for the browser to come back
+pub const LOOPBACK_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+const CALLBACK_PATH: &str = "/callback";
+
+const SUCCESS_PAGE: &str = "<!doctype html><meta charset=\"utf-8\"><title>KamiView</title>\
+    <p>Authorization complete. You can close this tab and return to KamiView.</p>";
+
+const FAILURE_PAGE: &str = "<!doctype html><meta charset=\"utf-8\"><title>KamiView</title>\
+    <p>Authorization failed. Return to KamiView and try again.</p>";
+
+/// Short-lived HTTP listener on 127.0.0.1 that receives the OAuth redirect
+pub struct LoopbackListener {
+    server: Server,
+    redirect_uri: String,
+    state: String,
+}
+
+impl LoopbackListener {
+    /// Listen on `port`; it has to match the redirect URI registered for the Shikimori app.
+    /// Port 0 picks a free one
+    pub fn bind(port: u16) -> Result<Self> {
+        let server = Server::http(("127.0.0.1", port))
+            .map_err(|e| anyhow::anyhow!("Failed to listen on 127.0.0.1:{}: {}", port, e))?;
+        let port = server.server_addr().to_ip().map_or(port, |addr| addr.port());
+
+        Ok(Self {
+            server,
+            redirect_uri: format!("http://127.0.0.1:{}{}", port, CALLBACK_PATH),
+            state: uuid::Uuid::new_v4().to_string(),
+        })
+    }
+
+    pub fn redirect_uri(&self) -> &str {
+        &self.redirect_uri
+    }
+
+    /// Random value the redirect has to carry back
+    pub fn state(&self) -> &str {
+        &self.state
+    }
+
+    /// Block until the redirect with a matching `state` arrives and return its code
+    pub fn wait_for_code(self, timeout: Duration) -> Result<String> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let Some(request) = self.server.recv_timeout(remaining)? else {
+                return Err(anyhow::anyhow!("Timed out waiting for the Shikimori redirect"));
+            };
+
+            let url = Url::parse(&format!("http://127.0.0.1{}", request.url()))?;
+            if url.path() != CALLBACK_PATH {
+                // Browsers also ask for /favicon.ico
+                let _ = request.respond(Response::empty(404));
+                continue;
+            }
+
+            let param = |name: &str| url.query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned());
+
+            if param("state").as_deref() != Some(self.state.as_str()) {
+                log::warn!("Ignoring OAuth redirect with a wrong state");
+                let _ = request.respond(html(FAILURE_PAGE, 400));
+                continue;
+            }
+
+            if let Some(error) = param("error") {
+                let _ = request.respond(html(FAILURE_PAGE, 400));
+                return Err(anyhow::anyhow!("Authorization denied: {}", error));
+            }
+
+            let Some(code) = param("code") else {
+                let _ = request.respond(html(FAILURE_PAGE, 400));
+                return Err(anyhow::anyhow!("Redirect has no authorization code"));
+            };
+
+            let _ = request.respond(html(SUCCESS_PAGE, 200));
+            return Ok(code);
+        }
+    }
+}
+
+fn html(body: &str, status: u16) -> Response<std::io::Cursor<Vec<u8>>> {
+    let header = Header::from_bytes("Content-Type", "text/html; charset=utf-8")
+        .expect("Failed to create content-type header");
+    Response::from_string(body)
+        .with_status_code(status)
+        .with_header(header)
+}
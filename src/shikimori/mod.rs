@@ -1,5 +1,9 @@
 pub mod client;
+mod anime;
+mod loopback;
+mod rates;
 mod storage;
+mod tokens;
 
 pub use client::{
     ShikimoriOAuth, 
@@ -8,14 +12,19 @@ pub use client::{
     ShikimoriClientParameters as ShikimoriClientConfig
 };
 pub use storage::{Storage, AuthTokens};
+pub use anime::{AnimeDetails, AnimeImage, Genre, Studio};
+pub use loopback::LoopbackListener;
+pub use rates::{RateChange, RateStatus, RatesData, RatesStore, SyncReport, UserRate};
 
 use shaku::Component;
-use crate::di::interfaces::{IShikimoriClient, IShikimoriOAuth};
+use crate::di::interfaces::{AuthEvent, IShikimoriClient, IShikimoriOAuth};
 use anyhow::Result;
 use std::sync::Arc;
 use std::future::Future;
 use std::pin::Pin;
 use reqwest::Client;
+use tokio::sync::broadcast;
+use rates::RateSync;
 
 #[derive(Debug)]
 pub struct ShikimoriOAuthParameters {
@@ -65,6 +74,46 @@ impl IShikimoriOAuth for ShikimoriOAuthComponent {
     fn get_auth_url(&self) -> Result<String> {
         Ok(self.oauth.get_auth_url())
     }
+
+    fn login_loopback(&self, port: u16) -> Pin<Box<dyn Future<Output = Result<TokenResponse>> + Send>> {
+        let oauth = self.oauth.clone();
+        Box::pin(async move {
+            oauth.login_loopback(port).await
+        })
+    }
+
+    fn access_token(&self) -> Pin<Box<dyn Future<Output = Result<String>> + Send>> {
+        let oauth = self.oauth.clone();
+        Box::pin(async move {
+            oauth.access_token().await
+        })
+    }
+
+    fn refresh_token(&self, rejected: &str) -> Pin<Box<dyn Future<Output = Result<String>> + Send>> {
+        let oauth = self.oauth.clone();
+        let rejected = rejected.to_string();
+        Box::pin(async move {
+            oauth.refresh_token(&rejected).await
+        })
+    }
+
+    fn is_logged_in(&self) -> Pin<Box<dyn Future<Output = bool> + Send>> {
+        let oauth = self.oauth.clone();
+        Box::pin(async move {
+            oauth.is_logged_in().await
+        })
+    }
+
+    fn keep_tokens_fresh(&self) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        let oauth = self.oauth.clone();
+        Box::pin(async move {
+            oauth.keep_tokens_fresh().await
+        })
+    }
+
+    fn subscribe_auth_events(&self) -> broadcast::Receiver<AuthEvent> {
+        self.oauth.subscribe_auth_events()
+    }
 }
 
 #[derive(Component)]
@@ -76,10 +125,20 @@ pub struct ShikimoriClient {
     client_id: String,
     #[shaku(default = String::new())]
     client_secret: String,
+    #[shaku(default = Arc::new(Client::new()))]
+    http: Arc<Client>,
+    #[shaku(default = Arc::new(RatesStore::default()))]
+    rates: Arc<RatesStore>,
     #[shaku(inject)]
     oauth: Arc<dyn IShikimoriOAuth>,
 }
 
+impl ShikimoriClient {
+    fn rate_sync(&self) -> RateSync {
+        RateSync::new(self.http.clone(), self.base_url.clone(), self.oauth.clone(), self.rates.clone())
+    }
+}
+
 impl IShikimoriClient for ShikimoriClient {
     fn get_user_info(&self) -> Pin<Box<dyn Future<Output = Result<UserInfo>> + Send>> {
         let oauth = self.oauth.clone();
@@ -90,8 +149,11 @@ impl IShikimoriClient for ShikimoriClient {
 
     fn logout(&self) -> Pin<Box<dyn Future<Output = Result<()>> + Send>> {
         let oauth = self.oauth.clone();
+        let rates = self.rates.clone();
         Box::pin(async move {
-            oauth.logout().await
+            oauth.logout().await?;
+            // The local list belongs to the account that logged out
+            rates.clear()
         })
     }
 
@@ -106,4 +168,46 @@ impl IShikimoriClient for ShikimoriClient {
     fn get_auth_url(&self) -> Result<String> {
         self.oauth.get_auth_url()
     }
+
+    fn login_loopback(&self, port: u16) -> Pin<Box<dyn Future<Output = Result<TokenResponse>> + Send>> {
+        self.oauth.login_loopback(port)
+    }
+
+    fn get_anime(&self, id: i64) -> Pin<Box<dyn Future<Output = Result<AnimeDetails>> + Send>> {
+        let http = self.http.clone();
+        let base_url = self.base_url.clone();
+        Box::pin(async move {
+            anime::fetch_anime(&http, &base_url, id).await
+        })
+    }
+
+    fn is_logged_in(&self) -> Pin<Box<dyn Future<Output = bool> + Send>> {
+        self.oauth.is_logged_in()
+    }
+
+    fn keep_tokens_fresh(&self) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        self.oauth.keep_tokens_fresh()
+    }
+
+    fn subscribe_auth_events(&self) -> broadcast::Receiver<AuthEvent> {
+        self.oauth.subscribe_auth_events()
+    }
+
+    fn get_user_rates(&self) -> Result<RatesData> {
+        self.rates.load()
+    }
+
+    fn sync_user_rates(&self) -> Pin<Box<dyn Future<Output = Result<SyncReport>> + Send>> {
+        let sync = self.rate_sync();
+        Box::pin(async move {
+            sync.sync().await
+        })
+    }
+
+    fn update_user_rate(&self, change: RateChange) -> Pin<Box<dyn Future<Output = Result<UserRate>> + Send>> {
+        let sync = self.rate_sync();
+        Box::pin(async move {
+            sync.update(change).await
+        })
+    }
 }
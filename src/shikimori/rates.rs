@@ -0,0 +1,401 @@
+use anyhow::Result;
+use parking_lot::Mutex;
+use reqwest::{Client, Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use crate::di::interfaces::IShikimoriOAuth;
+use crate::storage::{get_storage_path, read_json, update_json};
+use super::client::USER_AGENT;
+
+/// Page size for `/v2/user_rates` (the API maximum)
+const RATES_PAGE_LIMIT: usize = 1000;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RateStatus {
+    Planned,
+    Watching,
+    Rewatching,
+    Completed,
+    OnHold,
+    Dropped,
+}
+
+/// An anime entry of the user's list, as returned by `/v2/user_rates`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserRate {
+    /// `None` until a locally created rate reaches Shikimori
+    pub id: Option<i64>,
+    pub target_id: i64,
+    pub status: RateStatus,
+    #[serde(default)]
+    pub episodes: i32,
+    #[serde(default)]
+    pub score: i32,
+    #[serde(default)]
+    pub rewatches: i32,
+    pub updated_at: Option<String>,
+}
+
+impl UserRate {
+    /// `updated_at` as unix seconds, 0 if missing
+    pub fn updated_timestamp(&self) -> i64 {
+        self.updated_at.as_deref()
+            .and_then(|at| chrono::DateTime::parse_from_rfc3339(at).ok())
+            .map(|at| at.timestamp())
+            .unwrap_or(0)
+    }
+
+    fn apply(&mut self, change: &RateChange) {
+        if let Some(status) = change.status {
+            self.status = status;
+        }
+        if let Some(episodes) = change.episodes {
+            self.episodes = episodes;
+        }
+        if let Some(score) = change.score {
+            self.score = score;
+        }
+        self.updated_at = chrono::DateTime::from_timestamp(change.changed_at, 0).map(|at| at.to_rfc3339());
+    }
+}
+
+/// A local edit that hasn't reached Shikimori yet
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RateChange {
+    pub target_id: i64,
+    pub status: Option<RateStatus>,
+    pub episodes: Option<i32>,
+    pub score: Option<i32>,
+    /// Unix seconds of the edit, compared against the remote `updated_at`
+    pub changed_at: i64,
+}
+
+impl RateChange {
+    pub fn new(target_id: i64, status: Option<RateStatus>, episodes: Option<i32>, score: Option<i32>) -> Self {
+        Self {
+            target_id,
+            status,
+            episodes,
+            score,
+            changed_at: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    /// Fold a later edit of the same title into this one; fields it leaves unset are kept
+    pub fn merge(&mut self, newer: &RateChange) {
+        self.status = newer.status.or(self.status);
+        self.episodes = newer.episodes.or(self.episodes);
+        self.score = newer.score.or(self.score);
+        self.changed_at = newer.changed_at;
+    }
+
+    /// The remote rate was updated after this edit, so the edit is dropped
+    pub fn is_older_than(&self, remote: &UserRate) -> bool {
+        remote.updated_timestamp() > self.changed_at
+    }
+
+    fn user_rate_body(&self) -> serde_json::Value {
+        let mut body = serde_json::Map::new();
+        if let Some(status) = self.status {
+            body.insert("status".to_string(), json!(status));
+        }
+        if let Some(episodes) = self.episodes {
+            body.insert("episodes".to_string(), json!(episodes));
+        }
+        if let Some(score) = self.score {
+            body.insert("score".to_string(), json!(score));
+        }
+        serde_json::Value::Object(body)
+    }
+}
+
+/// Local copy of the list and the offline queue, stored next to the tokens
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RatesData {
+    pub user_id: Option<i64>,
+    pub rates: Vec<UserRate>,
+    pub pending: Vec<RateChange>,
+    pub synced_at: Option<i64>,
+}
+
+impl RatesData {
+    pub fn rate(&self, target_id: i64) -> Option<&UserRate> {
+        self.rates.iter().find(|r| r.target_id == target_id)
+    }
+
+    /// Apply an edit to the local list and queue it, merged with any queued edit of the same title
+    pub fn queue(&mut self, change: RateChange) -> UserRate {
+        match self.pending.iter_mut().find(|c| c.target_id == change.target_id) {
+            Some(queued) => queued.merge(&change),
+            None => self.pending.push(change.clone()),
+        }
+
+        let index = match self.rates.iter().position(|r| r.target_id == change.target_id) {
+            Some(index) => index,
+            None => {
+                self.rates.push(UserRate {
+                    id: None,
+                    target_id: change.target_id,
+                    status: RateStatus::Planned,
+                    episodes: 0,
+                    score: 0,
+                    rewatches: 0,
+                    updated_at: None,
+                });
+                self.rates.len() - 1
+            }
+        };
+        self.rates[index].apply(&change);
+        self.rates[index].clone()
+    }
+}
+
+/// Outcome of one synchronization
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SyncReport {
+    pub rates: usize,
+    /// Queued edits that reached Shikimori
+    pub pushed: usize,
+    /// Queued edits dropped because the remote rate was updated later
+    pub overridden: usize,
+    /// Queued edits dropped because Shikimori refused them for good (4xx),
+    /// e.g. a deleted rate or a duplicate
+    pub rejected: Vec<i64>,
+    /// Edits still queued, e.g. because Shikimori was unreachable
+    pub pending: usize,
+    pub offline: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct RatesStore {
+    /// `None`: `user_rates.json` next to the tokens
+    path: Option<PathBuf>,
+    lock: Mutex<()>,
+    /// Held for a whole sync, so two syncs never push the same queue
+    syncing: tokio::sync::Mutex<()>,
+}
+
+impl RatesStore {
+    /// Store backed by `path` instead of the default file
+    pub fn at(path: PathBuf) -> Self {
+        Self {
+            path: Some(path),
+            ..Self::default()
+        }
+    }
+
+    fn path(&self) -> PathBuf {
+        self.path.clone().unwrap_or_else(|| get_storage_path().with_file_name("user_rates.json"))
+    }
+
+    pub fn load(&self) -> Result<RatesData> {
+        let _guard = self.lock.lock();
+        read_json(&self.path())
+    }
+
+    /// Change the data under the lock and save it
+    pub fn update<T>(&self, change: impl FnOnce(&mut RatesData) -> T) -> Result<T> {
+        update_json(&self.path(), &self.lock, change)
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        let _guard = self.lock.lock();
+        let path = self.path();
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Two-way synchronization of the anime list with `/v2/user_rates`
+pub struct RateSync {
+    http: Arc<Client>,
+    base_url: String,
+    oauth: Arc<dyn IShikimoriOAuth>,
+    store: Arc<RatesStore>,
+}
+
+impl RateSync {
+    pub fn new(http: Arc<Client>, base_url: String, oauth: Arc<dyn IShikimoriOAuth>, store: Arc<RatesStore>) -> Self {
+        Self { http, base_url, oauth, store }
+    }
+
+    /// Change a rate locally and try to push it right away; it stays queued if that fails
+    pub async fn update(&self, change: RateChange) -> Result<UserRate> {
+        let rate = self.store.update(|data| data.queue(change))?;
+        if let Err(e) = self.sync().await {
+            log::warn!("User rate for {} queued, sync failed: {}", rate.target_id, e);
+        }
+        Ok(self.store.load()?.rate(rate.target_id).cloned().unwrap_or(rate))
+    }
+
+    /// Push queued edits and download the list
+    ///
+    /// A queued edit loses to the remote rate if that was updated after the edit.
+    /// Only one sync runs at a time, a second one waits for the first.
+    pub async fn sync(&self) -> Result<SyncReport> {
+        let _syncing = self.store.syncing.lock().await;
+        let user_id = self.user_id().await?;
+
+        let remote = match self.fetch_rates(user_id).await {
+            Ok(remote) => remote,
+            Err(e) if is_offline(&e) => {
+                log::warn!("Shikimori is unreachable, keeping queued rates: {}", e);
+                let data = self.store.load()?;
+                return Ok(SyncReport {
+                    rates: data.rates.len(),
+                    pending: data.pending.len(),
+                    offline: true,
+                    ..SyncReport::default()
+                });
+            },
+            Err(e) => return Err(e),
+        };
+        let mut remote: HashMap<i64, UserRate> = remote.into_iter().map(|r| (r.target_id, r)).collect();
+
+        let mut report = SyncReport::default();
+        let mut processed = HashSet::new();
+        for change in self.store.load()?.pending {
+            let existing = remote.get(&change.target_id).cloned();
+            if existing.as_ref().is_some_and(|r| change.is_older_than(r)) {
+                log::info!("Remote rate for {} is newer, dropping local edit", change.target_id);
+                report.overridden += 1;
+                processed.insert((change.target_id, change.changed_at));
+                continue;
+            }
+
+            match self.push(user_id, &change, existing.and_then(|r| r.id)).await {
+                Ok(rate) => {
+                    report.pushed += 1;
+                    remote.insert(rate.target_id, rate);
+                    processed.insert((change.target_id, change.changed_at));
+                },
+                Err(e) if is_offline(&e) => {
+                    log::warn!("Shikimori became unreachable during sync: {}", e);
+                    report.offline = true;
+                    break;
+                },
+                // Retrying would fail the same way and keep the edit winning every conflict
+                Err(e) if is_rejected(&e) => {
+                    log::error!("Shikimori rejected user rate for {}, dropping local edit: {}", change.target_id, e);
+                    report.rejected.push(change.target_id);
+                    processed.insert((change.target_id, change.changed_at));
+                },
+                Err(e) => log::error!("Failed to push user rate for {}: {}", change.target_id, e),
+            }
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        self.store.update(|data| {
+            // Edits queued while the sync was running are kept
+            data.pending.retain(|c| !processed.contains(&(c.target_id, c.changed_at)));
+
+            let mut rates: Vec<UserRate> = remote.into_values().collect();
+            rates.sort_by_key(|r| std::cmp::Reverse(r.updated_timestamp()));
+            data.rates = rates;
+            for change in data.pending.clone() {
+                if let Some(rate) = data.rates.iter_mut().find(|r| r.target_id == change.target_id) {
+                    rate.apply(&change);
+                }
+            }
+
+            data.synced_at = Some(now);
+            report.rates = data.rates.len();
+            report.pending = data.pending.len();
+        })?;
+
+        log::info!(
+            "User rates synced: {} rates, {} pushed, {} overridden, {} rejected, {} pending",
+            report.rates, report.pushed, report.overridden, report.rejected.len(), report.pending
+        );
+        Ok(report)
+    }
+
+    async fn user_id(&self) -> Result<i64> {
+        if let Some(user_id) = self.store.load()?.user_id {
+            return Ok(user_id);
+        }
+        let user = self.oauth.get_user_info().await?;
+        self.store.update(|data| data.user_id = Some(user.id))?;
+        Ok(user.id)
+    }
+
+    async fn fetch_rates(&self, user_id: i64) -> Result<Vec<UserRate>> {
+        let mut rates = Vec::new();
+        for page in 1.. {
+            let url = format!(
+                "{}/v2/user_rates?user_id={}&target_type=Anime&page={}&limit={}",
+                self.base_url, user_id, page, RATES_PAGE_LIMIT
+            );
+            let batch: Vec<UserRate> = self.request(Method::GET, &url, None).await?.json().await?;
+            let done = batch.len() < RATES_PAGE_LIMIT;
+            rates.extend(batch);
+            if done {
+                break;
+            }
+        }
+        Ok(rates)
+    }
+
+    async fn push(&self, user_id: i64, change: &RateChange, rate_id: Option<i64>) -> Result<UserRate> {
+        let mut user_rate = change.user_rate_body();
+        let (method, url) = match rate_id {
+            Some(rate_id) => (Method::PATCH, format!("{}/v2/user_rates/{}", self.base_url, rate_id)),
+            None => {
+                user_rate["user_id"] = json!(user_id);
+                user_rate["target_id"] = json!(change.target_id);
+                user_rate["target_type"] = json!("Anime");
+                (Method::POST, format!("{}/v2/user_rates", self.base_url))
+            },
+        };
+
+        let body = json!({ "user_rate": user_rate });
+        Ok(self.request(method, &url, Some(&body)).await?.json().await?)
+    }
+
+    /// Authorized request, refreshing the token once on 401
+    async fn request(&self, method: Method, url: &str, body: Option<&serde_json::Value>) -> Result<reqwest::Response> {
+        let send = |token: String| {
+            let mut request = self.http
+                .request(method.clone(), url)
+                .header("User-Agent", USER_AGENT)
+                .bearer_auth(token);
+            if let Some(body) = body {
+                request = request.json(body);
+            }
+            request.send()
+        };
+
+        let access_token = self.oauth.access_token().await?;
+        let mut response = send(access_token.clone()).await?;
+        if response.status() == StatusCode::UNAUTHORIZED {
+            log::warn!("Access token rejected, attempting to refresh");
+            response = send(self.oauth.refresh_token(&access_token).await?).await?;
+        }
+        Ok(response.error_for_status()?)
+    }
+}
+
+/// Connection problems keep edits queued; other errors are reported
+fn is_offline(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<reqwest::Error>()
+        .is_some_and(|e| e.is_connect() || e.is_timeout() || e.status().is_some_and(|s| s.is_server_error()))
+}
+
+/// A 4xx that won't go away on retry, e.g. 404 for a deleted rate or 422 for a duplicate;
+/// auth failures, timeouts and rate limiting stay queued
+fn is_rejected(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<reqwest::Error>()
+        .and_then(reqwest::Error::status)
+        .is_some_and(|s| s.is_client_error() && !matches!(
+            s,
+            StatusCode::UNAUTHORIZED | StatusCode::REQUEST_TIMEOUT | StatusCode::TOO_MANY_REQUESTS
+        ))
+}
@@ -12,6 +12,17 @@ pub struct AuthTokens {
     pub expires_at: u64,
 }
 
+impl AuthTokens {
+    /// Time left until `expires_at`, zero once expired
+    pub fn expires_in(&self) -> std::time::Duration {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        std::time::Duration::from_secs(self.expires_at.saturating_sub(now))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Storage {
     pub path: PathBuf,
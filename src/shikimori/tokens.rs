@@ -0,0 +1,214 @@
+use anyhow::Result;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, Mutex, Notify, RwLock};
+use crate::di::interfaces::AuthEvent;
+use super::client::{TokenResponse, TOKEN_URL, USER_AGENT};
+use super::storage::{AuthTokens, Storage};
+
+/// Refresh the access token this long before it expires
+pub const REFRESH_AHEAD: Duration = Duration::from_secs(5 * 60);
+/// Delay before the background refresher retries after a network error
+const REFRESH_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// The refresh token was revoked or has expired, only a new login helps
+#[derive(Debug)]
+pub struct ReloginRequired;
+
+impl fmt::Display for ReloginRequired {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Shikimori session expired, log in again")
+    }
+}
+
+impl std::error::Error for ReloginRequired {}
+
+/// OAuth error body, e.g. `{"error": "invalid_grant", "error_description": "..."}`
+#[derive(Debug, Deserialize)]
+struct OAuthError {
+    error: String,
+}
+
+/// Owns the Shikimori tokens: hands out access tokens, refreshes them ahead of
+/// `expires_at` and lets only one refresh run at a time
+#[derive(Debug)]
+pub struct TokenManager {
+    client: Arc<Client>,
+    storage: Storage,
+    tokens: RwLock<Option<AuthTokens>>,
+    /// Held for the whole refresh, so concurrent callers reuse its result;
+    /// login and logout take it too, so a running refresh can't undo them
+    refreshing: Mutex<()>,
+    /// Wakes the background refresher when tokens are replaced or removed
+    changed: Notify,
+    events: broadcast::Sender<AuthEvent>,
+}
+
+impl TokenManager {
+    pub fn new(client: Arc<Client>, storage: Storage) -> Result<Self> {
+        let tokens = storage.load_auth_tokens()?;
+        if tokens.is_some() {
+            log::info!("Loaded saved token");
+        }
+
+        Ok(Self {
+            client,
+            storage,
+            tokens: RwLock::new(tokens),
+            refreshing: Mutex::new(()),
+            changed: Notify::new(),
+            events: broadcast::channel(4).0,
+        })
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<AuthEvent> {
+        self.events.subscribe()
+    }
+
+    pub async fn is_logged_in(&self) -> bool {
+        self.tokens.read().await.is_some()
+    }
+
+    /// Store tokens from a fresh login
+    pub async fn set(&self, token: &TokenResponse) -> Result<()> {
+        let _guard = self.refreshing.lock().await;
+        self.store(token).await
+    }
+
+    pub async fn clear(&self) -> Result<()> {
+        let _guard = self.refreshing.lock().await;
+        self.remove().await
+    }
+
+    /// Access token for an API call, refreshed first if it is about to expire
+    pub async fn access_token(&self) -> Result<String> {
+        let tokens = self.current().await?;
+        if tokens.expires_in() > REFRESH_AHEAD {
+            return Ok(tokens.access_token);
+        }
+
+        match self.refresh_after(&tokens.access_token).await {
+            Ok(access_token) => Ok(access_token),
+            Err(e) if e.is::<ReloginRequired>() => Err(e),
+            // Offline: the old token may still be accepted, the call will tell
+            Err(e) => {
+                log::warn!("Failed to refresh token ahead of expiry: {}", e);
+                Ok(tokens.access_token)
+            }
+        }
+    }
+
+    /// New access token after `rejected` got a 401
+    ///
+    /// If another caller already replaced `rejected`, its token is returned
+    /// instead of refreshing a second time.
+    pub async fn refresh_after(&self, rejected: &str) -> Result<String> {
+        let _guard = self.refreshing.lock().await;
+
+        let tokens = self.current().await?;
+        if tokens.access_token != rejected {
+            return Ok(tokens.access_token);
+        }
+        self.refresh(&tokens.refresh_token).await
+    }
+
+    /// Refresh ahead of expiry until the app exits; idles while logged out
+    pub async fn run(&self) {
+        loop {
+            // Registered before reading, so a login in between is not missed
+            let changed = self.changed.notified();
+            let tokens = self.tokens.read().await.clone();
+
+            let Some(tokens) = tokens else {
+                changed.await;
+                continue;
+            };
+
+            let wait = tokens.expires_in().saturating_sub(REFRESH_AHEAD);
+            if !wait.is_zero() {
+                tokio::select! {
+                    _ = tokio::time::sleep(wait) => {},
+                    _ = changed => continue,
+                }
+            }
+
+            match self.refresh_after(&tokens.access_token).await {
+                Ok(_) => log::debug!("Refreshed Shikimori token ahead of expiry"),
+                // Tokens are gone now, the next iteration waits for a login
+                Err(e) if e.is::<ReloginRequired>() => {},
+                Err(e) => {
+                    log::warn!("Background token refresh failed: {}", e);
+                    tokio::time::sleep(REFRESH_RETRY_DELAY).await;
+                }
+            }
+        }
+    }
+
+    /// Replace the tokens; the caller holds `refreshing`
+    async fn store(&self, token: &TokenResponse) -> Result<()> {
+        let tokens = AuthTokens::from(token.clone());
+        self.storage.save_auth_tokens(&tokens)?;
+        *self.tokens.write().await = Some(tokens);
+        self.changed.notify_waiters();
+        Ok(())
+    }
+
+    /// Forget the tokens; the caller holds `refreshing`
+    async fn remove(&self) -> Result<()> {
+        *self.tokens.write().await = None;
+        self.changed.notify_waiters();
+        self.storage.delete_auth_tokens()
+    }
+
+    async fn current(&self) -> Result<AuthTokens> {
+        self.tokens.read().await
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("No access token available"))
+    }
+
+    /// Exchange the refresh token; the caller holds `refreshing`
+    async fn refresh(&self, refresh_token: &str) -> Result<String> {
+        log::info!("Refreshing token");
+
+        let response = self.client
+            .post(TOKEN_URL)
+            .header("User-Agent", USER_AGENT)
+            .form(&[
+                ("grant_type", "refresh_token"),
+                ("client_id", self.storage.shikimori_client_id()),
+                ("client_secret", self.storage.shikimori_client_secret()),
+                ("refresh_token", refresh_token),
+            ])
+            .send()
+            .await?;
+
+        let status = response.status();
+        if matches!(status, StatusCode::BAD_REQUEST | StatusCode::UNAUTHORIZED) {
+            let body = response.text().await.unwrap_or_default();
+            let error = serde_json::from_str::<OAuthError>(&body).map(|e| e.error).unwrap_or_default();
+
+            // Only `invalid_grant` means the refresh token was revoked or already used;
+            // anything else (a bad client secret, a proxy page) keeps the session
+            if error != "invalid_grant" {
+                return Err(anyhow::anyhow!("Token refresh failed with {}: {}", status, body));
+            }
+            log::warn!("Shikimori rejected the refresh token: {}", body);
+
+            self.remove().await?;
+            let _ = self.events.send(AuthEvent::ReloginRequired {
+                reason: ReloginRequired.to_string(),
+            });
+            return Err(ReloginRequired.into());
+        }
+
+        let token = response.error_for_status()?.json::<TokenResponse>().await?;
+        log::info!("Successfully refreshed token");
+        log::debug!("New token expires in {} seconds", token.expires_in);
+
+        self.store(&token).await?;
+        Ok(token.access_token)
+    }
+}
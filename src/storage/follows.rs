@@ -0,0 +1,198 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use anyhow::Result;
+use directories::ProjectDirs;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use shaku::Component;
+use crate::di::interfaces::IFollowStore;
+use crate::kodik::{AvailableTranslation, MediaId};
+
+/// Сколько последних обновлений хранится в ленте
+pub const MAX_FOLLOW_UPDATES: usize = 200;
+
+/// Перевод отслеживаемого тайтла и число серий при последней проверке
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SeenTranslation {
+    pub title: String,
+    pub episodes: i32,
+}
+
+/// Отслеживаемый тайтл
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FollowedTitle {
+    pub id: MediaId,
+    pub title: String,
+    /// Переводы по id перевода Kodik
+    #[serde(default)]
+    pub translations: BTreeMap<i64, SeenTranslation>,
+    pub followed_at: u64,
+    #[serde(default)]
+    pub checked_at: Option<u64>,
+}
+
+impl FollowedTitle {
+    pub fn new(id: MediaId, title: String, translations: &[AvailableTranslation]) -> Self {
+        let mut followed = Self {
+            id,
+            title,
+            translations: BTreeMap::new(),
+            followed_at: unix_now(),
+            checked_at: None,
+        };
+        followed.apply(translations);
+        followed
+    }
+
+    /// Сравнить переводы с последней проверкой и запомнить новые значения
+    ///
+    /// Возвращает по обновлению на каждый перевод, у которого стало больше серий.
+    /// Новые переводы запоминаются без обновления: до подписки о них ничего не известно.
+    pub fn apply(&mut self, translations: &[AvailableTranslation]) -> Vec<FollowUpdate> {
+        let now = unix_now();
+        let is_first_check = self.translations.is_empty();
+        let mut updates = Vec::new();
+
+        for translation in translations {
+            let episodes = translation.episodes_count.or(translation.last_episode).unwrap_or(0);
+            let previous = self.translations.insert(translation.id, SeenTranslation {
+                title: translation.title.clone(),
+                episodes,
+            });
+
+            let previous_episodes = match previous {
+                Some(previous) => previous.episodes,
+                None if is_first_check => continue,
+                None => 0,
+            };
+            if episodes > previous_episodes {
+                updates.push(FollowUpdate {
+                    id: self.id.clone(),
+                    title: self.title.clone(),
+                    translation_id: translation.id,
+                    translation_title: translation.title.clone(),
+                    previous_episodes,
+                    episodes,
+                    detected_at: now,
+                    seen: false,
+                });
+            }
+        }
+
+        self.checked_at = Some(now);
+        updates
+    }
+}
+
+/// Новая серия отслеживаемого тайтла в одном из переводов
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FollowUpdate {
+    pub id: MediaId,
+    pub title: String,
+    pub translation_id: i64,
+    pub translation_title: String,
+    pub previous_episodes: i32,
+    pub episodes: i32,
+    pub detected_at: u64,
+    #[serde(default)]
+    pub seen: bool,
+}
+
+/// Содержимое `follows.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FollowsData {
+    #[serde(default)]
+    pub titles: Vec<FollowedTitle>,
+    /// Лента обновлений, новые в начале
+    #[serde(default)]
+    pub updates: Vec<FollowUpdate>,
+}
+
+#[derive(Component)]
+#[shaku(interface = IFollowStore)]
+pub struct FollowStore {
+    #[shaku(default)]
+    path: PathBuf,
+    /// Сериализует чтение-изменение-запись файла
+    #[shaku(default)]
+    lock: Mutex<()>,
+}
+
+impl FollowStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            lock: Mutex::new(()),
+        }
+    }
+
+    pub fn initialize_path() -> PathBuf {
+        let project_dirs = ProjectDirs::from("com", "kamiview", "KamiView")
+            .expect("Failed to get project directories");
+
+        let config_dir = project_dirs.config_dir();
+        fs::create_dir_all(config_dir).expect("Failed to create config directory");
+
+        config_dir.join("follows.json")
+    }
+
+    /// Изменить данные под блокировкой и сохранить их
+    fn update<T>(&self, change: impl FnOnce(&mut FollowsData) -> T) -> Result<T> {
+        super::update_json(&self.path, &self.lock, change)
+    }
+}
+
+impl IFollowStore for FollowStore {
+    fn load(&self) -> Result<FollowsData> {
+        let _guard = self.lock.lock();
+        super::read_json(&self.path)
+    }
+
+    fn follow(&self, id: MediaId, title: String, translations: &[AvailableTranslation]) -> Result<FollowedTitle> {
+        self.update(|data| {
+            let followed = FollowedTitle::new(id, title, translations);
+            data.titles.retain(|t| t.id != followed.id);
+            data.titles.push(followed.clone());
+            followed
+        })
+    }
+
+    fn unfollow(&self, id: &MediaId) -> Result<bool> {
+        self.update(|data| {
+            let before = data.titles.len();
+            data.titles.retain(|t| &t.id != id);
+            data.updates.retain(|u| &u.id != id);
+            data.titles.len() != before
+        })
+    }
+
+    fn record(&self, id: &MediaId, translations: &[AvailableTranslation]) -> Result<Vec<FollowUpdate>> {
+        self.update(|data| {
+            // Тайтл могли отписать, пока шла проверка
+            let Some(followed) = data.titles.iter_mut().find(|t| &t.id == id) else {
+                return Vec::new();
+            };
+            let updates = followed.apply(translations);
+
+            for update in updates.iter().rev() {
+                data.updates.insert(0, update.clone());
+            }
+            data.updates.truncate(MAX_FOLLOW_UPDATES);
+            updates
+        })
+    }
+
+    fn mark_seen(&self) -> Result<()> {
+        self.update(|data| {
+            for update in &mut data.updates {
+                update.seen = true;
+            }
+        })
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
@@ -1,17 +1,41 @@
 use std::fs;
-use std::path::PathBuf;
+use std::io;
+use std::path::{Path, PathBuf};
 use directories::ProjectDirs;
+use parking_lot::Mutex;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use iced::Theme;
 use crate::di::interfaces::IStorage;
 use shaku::Component;
 use std::fmt;
 use std::sync::OnceLock;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+
+mod follows;
+
+pub use follows::{FollowStore, FollowStoreParameters, FollowedTitle, FollowUpdate, FollowsData, SeenTranslation, MAX_FOLLOW_UPDATES};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AppSettings {
     pub theme: ThemeType,
+    /// Предпочитаемое качество видео (360, 480, 720...). `None` — максимальное доступное
+    #[serde(default)]
+    pub preferred_quality: Option<i32>,
+    /// Отмечать просмотренные серии в списке Shikimori; выключено, пока его не включат в настройках
+    #[serde(default = "default_auto_scrobble")]
+    pub auto_scrobble: bool,
+    /// Доля серии (0..1), после которой она считается просмотренной
+    #[serde(default = "default_scrobble_threshold")]
+    pub scrobble_threshold: f64,
+}
+
+fn default_auto_scrobble() -> bool {
+    false
+}
+
+fn default_scrobble_threshold() -> f64 {
+    0.85
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
@@ -33,6 +57,9 @@ impl Default for AppSettings {
     fn default() -> Self {
         Self {
             theme: ThemeType::Light,
+            preferred_quality: None,
+            auto_scrobble: default_auto_scrobble(),
+            scrobble_threshold: default_scrobble_threshold(),
         }
     }
 }
@@ -112,4 +139,43 @@ pub fn initialize_storage_path() {
 
 pub fn get_storage_path() -> &'static PathBuf {
     STORAGE_PATH.get().expect("Storage path not initialized")
-}
\ No newline at end of file
+}
+
+/// Записать файл через временный рядом с ним и переименование,
+/// чтобы сбой посреди записи не оставил файл обрезанным
+pub(crate) fn write_atomic(path: &Path, contents: &str) -> Result<()> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+
+    fs::write(&temp, contents)?;
+    fs::rename(&temp, path)?;
+    Ok(())
+}
+/// Прочитать JSON-файл: нет файла — данные по умолчанию; файл, который не удалось
+/// прочитать или разобрать, — ошибка, чтобы следующая запись его не затёрла
+pub fn read_json<T: DeserializeOwned + Default>(path: &Path) -> Result<T> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(T::default()),
+        Err(e) => return Err(anyhow!("Failed to read {}: {}", path.display(), e)),
+    };
+    serde_json::from_str(&contents)
+        .map_err(|e| anyhow!("Failed to parse {}: {}", path.display(), e))
+}
+
+/// Прочитать JSON-файл, изменить данные и сохранить их, всё под блокировкой `lock`
+pub fn update_json<T, R>(path: &Path, lock: &Mutex<()>, change: impl FnOnce(&mut T) -> R) -> Result<R>
+where
+    T: Serialize + DeserializeOwned + Default,
+{
+    let _guard = lock.lock();
+    let mut data = read_json(path)?;
+    let result = change(&mut data);
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    write_atomic(path, &serde_json::to_string_pretty(&data)?)?;
+    Ok(result)
+}
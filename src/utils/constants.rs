@@ -9,6 +9,8 @@ pub struct Config {
     pub shikimori_client_id: String,
     pub shikimori_client_secret: String,
     pub mpv_socket_path: &'static str,
+    /// Port for the loopback OAuth redirect; without it the code is pasted by hand
+    pub shikimori_redirect_port: Option<u16>,
 }
 
 impl Config {
@@ -23,6 +25,9 @@ impl Config {
             } else {
                 "/tmp/mpv-socket"
             },
+            shikimori_redirect_port: env::var("SHIKIMORI_REDIRECT_PORT")
+                .ok()
+                .and_then(|port| port.trim().parse().ok()),
         }
     }
 }
\ No newline at end of file
@@ -1,5 +1,6 @@
 // Backend API routes
 pub const API_SEARCH: &str = "search";
+pub const API_BROWSE: &str = "browse";
 pub const API_ANIME_SELECTED: &str = "animeSelected";
 pub const API_PLAY_EPISODE: &str = "playEpisode";
 pub const API_GET_PLAYBACK_INFO: &str = "getPlaybackInfo";
@@ -9,4 +10,19 @@ pub const API_START_DOWNLOAD: &str = "startDownload";
 pub const API_EXCHANGE_CODE: &str = "exchangeCode";
 pub const API_GET_USER_INFO: &str = "getUserInfo";
 pub const API_LOGOUT: &str = "logout";
-pub const API_OPEN_AUTH_URL: &str = "openAuthUrl"; 
\ No newline at end of file
+pub const API_OPEN_AUTH_URL: &str = "openAuthUrl"; 
+pub const API_UPDATE_SETTINGS: &str = "updateSettings";
+pub const API_GET_KODIK_STATUS: &str = "getKodikStatus";
+pub const API_CREATE_PLAYLIST: &str = "createPlaylist";
+pub const API_CANCEL_PLAYLIST: &str = "cancelPlaylist";
+pub const API_EXPORT_PLAYLIST: &str = "exportPlaylist";
+pub const API_FOLLOW_TITLE: &str = "followTitle";
+pub const API_UNFOLLOW_TITLE: &str = "unfollowTitle";
+pub const API_GET_FOLLOWS: &str = "getFollows";
+pub const API_CHECK_FOLLOWS: &str = "checkFollows";
+pub const API_MARK_FOLLOW_UPDATES_SEEN: &str = "markFollowUpdatesSeen";
+pub const API_GET_ANIME_DETAILS: &str = "getAnimeDetails";
+pub const API_GET_USER_RATES: &str = "getUserRates";
+pub const API_SYNC_USER_RATES: &str = "syncUserRates";
+pub const API_UPDATE_USER_RATE: &str = "updateUserRate";
+pub const API_START_LOOPBACK_LOGIN: &str = "startLoopbackLogin";
\ No newline at end of file
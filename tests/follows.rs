@@ -0,0 +1,55 @@
+use kami_view::kodik::{AvailableTranslation, MediaId, TranslationKind};
+use kami_view::storage::FollowedTitle;
+
+fn translation(id: i64, episodes: i32) -> AvailableTranslation {
+    AvailableTranslation {
+        id,
+        title: format!("Перевод {}", id),
+        translation_type: TranslationKind::Voice,
+        episodes_count: Some(episodes),
+        last_season: Some(1),
+        last_episode: Some(episodes),
+        updated_at: None,
+    }
+}
+
+fn followed(translations: &[AvailableTranslation]) -> FollowedTitle {
+    FollowedTitle::new(MediaId::Shikimori("52991".to_string()), "Frieren".to_string(), translations)
+}
+
+#[test]
+fn first_check_is_a_baseline() {
+    let mut title = followed(&[]);
+    assert!(title.checked_at.is_some());
+
+    // До первой проверки ничего не известно, вышедшие серии — не новость
+    assert!(title.apply(&[translation(610, 12), translation(609, 10)]).is_empty());
+    assert_eq!(title.translations[&610].episodes, 12);
+    assert_eq!(title.translations[&609].episodes, 10);
+}
+
+#[test]
+fn new_translation_is_reported_from_zero() {
+    let mut title = followed(&[translation(610, 12)]);
+
+    let updates = title.apply(&[translation(610, 12), translation(869, 3)]);
+    assert_eq!(updates.len(), 1);
+    assert_eq!(updates[0].translation_id, 869);
+    assert_eq!(updates[0].previous_episodes, 0);
+    assert_eq!(updates[0].episodes, 3);
+    assert!(!updates[0].seen);
+}
+
+#[test]
+fn episode_growth_is_reported_once() {
+    let mut title = followed(&[translation(610, 12), translation(609, 10)]);
+
+    let updates = title.apply(&[translation(610, 13), translation(609, 10)]);
+    assert_eq!(updates.len(), 1);
+    assert_eq!(updates[0].translation_id, 610);
+    assert_eq!((updates[0].previous_episodes, updates[0].episodes), (12, 13));
+
+    // Те же или меньшие числа серий — ничего нового
+    assert!(title.apply(&[translation(610, 13), translation(609, 10)]).is_empty());
+    assert!(title.apply(&[translation(610, 11)]).is_empty());
+}
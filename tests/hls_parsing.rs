@@ -0,0 +1,114 @@
+use kami_view::hls::{self, HlsPlaylist, MasterPlaylist, Resolution, VariantPreference};
+use kami_view::kodik::parsing;
+
+const BASE_URL: &str = "https://cloud.kodik-storage.com/useruploads/3f2e1d0c/720.mp4:hls:manifest.m3u8";
+
+const MASTER: &str = "#EXTM3U
+#EXT-X-VERSION:3
+#EXT-X-STREAM-INF:BANDWIDTH=800000,RESOLUTION=640x360,CODECS=\"avc1.4d401e,mp4a.40.2\"
+360/index.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=1400000,AVERAGE-BANDWIDTH=1200000,RESOLUTION=854x480,FRAME-RATE=23.976
+480/index.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=2800000,RESOLUTION=1280x720
+https://cdn.example.com/720/index.m3u8
+";
+
+const MASTER_WITHOUT_RESOLUTION: &str = "#EXTM3U
+#EXT-X-STREAM-INF:BANDWIDTH=800000
+low/index.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=2800000
+high/index.m3u8
+";
+
+const MEDIA: &str = "#EXTM3U
+#EXT-X-TARGETDURATION:10
+#EXT-X-MEDIA-SEQUENCE:1
+#EXTINF:10.0,
+seg-1-v1-a1.ts
+#EXTINF:10.0,
+seg-2-v1-a1.ts
+#EXTINF:4.5,last
+/useruploads/3f2e1d0c/seg-3-v1-a1.ts
+#EXT-X-ENDLIST
+";
+
+fn master() -> MasterPlaylist {
+    match hls::parse(MASTER, BASE_URL).unwrap() {
+        HlsPlaylist::Master(master) => master,
+        other => panic!("expected master playlist, got {:?}", other),
+    }
+}
+
+#[test]
+fn master_playlist_variants() {
+    let master = master();
+
+    assert_eq!(master.variants.len(), 3);
+    assert_eq!(master.variants[0].uri, "https://cloud.kodik-storage.com/useruploads/3f2e1d0c/360/index.m3u8");
+    assert_eq!(master.variants[0].codecs.as_deref(), Some("avc1.4d401e,mp4a.40.2"));
+    assert_eq!(master.variants[1].resolution, Some(Resolution { width: 854, height: 480 }));
+    assert_eq!(master.variants[1].average_bandwidth, Some(1_200_000));
+    assert_eq!(master.variants[1].frame_rate, Some(23.976));
+    assert_eq!(master.variants[2].uri, "https://cdn.example.com/720/index.m3u8");
+}
+
+#[test]
+fn variant_selection() {
+    let master = master();
+    let height = |preference| master.select(preference).map(|v| v.height());
+
+    assert_eq!(height(VariantPreference::Highest), Some(720));
+    assert_eq!(height(VariantPreference::MaxHeight(480)), Some(480));
+    assert_eq!(height(VariantPreference::MaxHeight(240)), Some(360));
+    assert_eq!(height(VariantPreference::MaxBandwidth(2_000_000)), Some(480));
+}
+
+#[test]
+fn variant_selection_without_resolution() {
+    let unsized_master = match hls::parse(MASTER_WITHOUT_RESOLUTION, BASE_URL).unwrap() {
+        HlsPlaylist::Master(master) => master,
+        other => panic!("expected master playlist, got {:?}", other),
+    };
+    let uri = |preference| unsized_master.select(preference).map(|v| v.uri.as_str());
+
+    // Высота неизвестна ни у одного варианта: выбор по битрейту
+    assert_eq!(uri(VariantPreference::MaxHeight(480)), uri(VariantPreference::Highest));
+    assert_eq!(uri(VariantPreference::MaxHeight(480)), Some("https://cloud.kodik-storage.com/useruploads/3f2e1d0c/high/index.m3u8"));
+
+    // Вариант без разрешения не проходит ограничение по высоте, если у остальных оно есть
+    let mut mixed = master();
+    mixed.variants[2].resolution = None;
+    assert_eq!(mixed.select(VariantPreference::MaxHeight(720)).map(|v| v.height()), Some(480));
+}
+
+#[test]
+fn kodik_mp4_behind_variant() {
+    assert_eq!(
+        parsing::mp4_url_of(BASE_URL).as_deref(),
+        Some("https://cloud.kodik-storage.com/useruploads/3f2e1d0c/720.mp4/")
+    );
+    assert_eq!(parsing::mp4_url_of("https://cdn.example.com/720/index.m3u8"), None);
+}
+
+#[test]
+fn media_playlist_segments() {
+    let media = match hls::parse(MEDIA, BASE_URL).unwrap() {
+        HlsPlaylist::Media(media) => media,
+        other => panic!("expected media playlist, got {:?}", other),
+    };
+
+    assert_eq!(media.target_duration, Some(10.0));
+    assert_eq!(media.media_sequence, 1);
+    assert!(media.end_list);
+    assert_eq!(media.segments.len(), 3);
+    assert_eq!(media.segments[0].uri, "https://cloud.kodik-storage.com/useruploads/3f2e1d0c/seg-1-v1-a1.ts");
+    assert_eq!(media.segments[2].uri, "https://cloud.kodik-storage.com/useruploads/3f2e1d0c/seg-3-v1-a1.ts");
+    assert_eq!(media.segments[2].title.as_deref(), Some("last"));
+    assert_eq!(media.duration(), 24.5);
+}
+
+#[test]
+fn invalid_playlists() {
+    assert!(hls::parse("<html></html>", BASE_URL).is_err());
+    assert!(hls::parse("#EXTM3U\n#EXT-X-VERSION:3\n", BASE_URL).is_err());
+}
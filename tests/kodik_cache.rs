@@ -0,0 +1,185 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::Context;
+use kami_view::kodik::{CacheConfig, CacheKind, KodikError, ResponseCache, SharedError};
+
+fn memory_only(ttl: Duration) -> CacheConfig {
+    CacheConfig {
+        info_ttl: ttl,
+        disk_dir: None,
+        ..CacheConfig::default()
+    }
+}
+
+async fn fetch_counted(cache: &ResponseCache, calls: &AtomicUsize, value: &str) -> anyhow::Result<String> {
+    cache.get_or_fetch(CacheKind::Info, "shikimori:20", || async {
+        calls.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        Ok(value.to_string())
+    }).await
+}
+
+#[tokio::test]
+async fn fresh_value_is_reused() {
+    let cache = ResponseCache::new(memory_only(Duration::from_secs(60)));
+    let calls = AtomicUsize::new(0);
+
+    assert_eq!(fetch_counted(&cache, &calls, "first").await.unwrap(), "first");
+    assert_eq!(fetch_counted(&cache, &calls, "second").await.unwrap(), "first");
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    cache.invalidate(CacheKind::Info, "shikimori:20");
+    assert_eq!(fetch_counted(&cache, &calls, "third").await.unwrap(), "third");
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn expired_value_is_fetched_again() {
+    let cache = ResponseCache::new(memory_only(Duration::from_secs(1)));
+    let calls = AtomicUsize::new(0);
+
+    fetch_counted(&cache, &calls, "first").await.unwrap();
+    // TTL считается в целых секундах
+    tokio::time::sleep(Duration::from_millis(2100)).await;
+    assert_eq!(fetch_counted(&cache, &calls, "second").await.unwrap(), "second");
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn zero_ttl_is_not_cached() {
+    let cache = ResponseCache::new(memory_only(Duration::ZERO));
+    let calls = AtomicUsize::new(0);
+
+    fetch_counted(&cache, &calls, "first").await.unwrap();
+    fetch_counted(&cache, &calls, "second").await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn concurrent_requests_are_coalesced() {
+    let cache = Arc::new(ResponseCache::new(memory_only(Duration::from_secs(60))));
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let tasks: Vec<_> = (0..5).map(|i| {
+        let cache = cache.clone();
+        let calls = calls.clone();
+        tokio::spawn(async move { fetch_counted(&cache, &calls, &format!("value {}", i)).await })
+    }).collect();
+
+    let mut values = Vec::new();
+    for task in tasks {
+        values.push(task.await.unwrap().unwrap());
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert!(values.iter().all(|value| *value == values[0]));
+}
+
+#[tokio::test]
+async fn failed_fetch_is_shared_and_then_retried() {
+    let cache = Arc::new(ResponseCache::new(memory_only(Duration::from_secs(60))));
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let tasks: Vec<_> = (0..4).map(|_| {
+        let cache = cache.clone();
+        let calls = calls.clone();
+        tokio::spawn(async move {
+            cache.get_or_fetch(CacheKind::Info, "shikimori:20", || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                Err::<String, _>(anyhow::anyhow!("Kodik is down"))
+            }).await
+        })
+    }).collect();
+
+    for task in tasks {
+        let error = task.await.unwrap().unwrap_err();
+        assert_eq!(error.to_string(), "Kodik is down");
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // Ошибка не кэшируется: следующий запрос идёт в Kodik снова
+    assert_eq!(fetch_counted(&cache, &calls, "recovered").await.unwrap(), "recovered");
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+async fn fetch_failing(cache: &ResponseCache, calls: &AtomicUsize) -> anyhow::Result<String> {
+    cache.get_or_fetch(CacheKind::Info, "shikimori:20", || async {
+        calls.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        Err::<String, _>(KodikError::ServiceError("502".into()))
+            .context("Не удалось получить информацию")
+    }).await
+}
+
+#[tokio::test]
+async fn shared_error_keeps_variant_and_context() {
+    let cache = Arc::new(ResponseCache::new(memory_only(Duration::from_secs(60))));
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let tasks: Vec<_> = (0..3).map(|_| {
+        let cache = cache.clone();
+        let calls = calls.clone();
+        tokio::spawn(async move { fetch_failing(&cache, &calls).await })
+    }).collect();
+
+    for task in tasks {
+        let error = task.await.unwrap().unwrap_err();
+        assert_eq!(error.to_string(), "Не удалось получить информацию");
+        assert!(error.chain().any(|cause| cause.to_string() == "Service error: 502"));
+
+        let shared = error.downcast_ref::<SharedError>().expect("shared error");
+        assert!(matches!(shared.inner().downcast_ref::<KodikError>(), Some(KodikError::ServiceError(_))));
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn single_failure_is_returned_unchanged() {
+    let cache = ResponseCache::new(memory_only(Duration::from_secs(60)));
+    let calls = AtomicUsize::new(0);
+
+    let error = fetch_failing(&cache, &calls).await.unwrap_err();
+    assert!(matches!(error.downcast_ref::<KodikError>(), Some(KodikError::ServiceError(_))));
+}
+
+#[tokio::test]
+async fn request_after_failure_is_not_served_stale_error() {
+    let cache = Arc::new(ResponseCache::new(memory_only(Duration::from_secs(60))));
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let leader = {
+        let (cache, calls) = (cache.clone(), calls.clone());
+        tokio::spawn(async move { fetch_failing(&cache, &calls).await })
+    };
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    let waiter = {
+        let (cache, calls) = (cache.clone(), calls.clone());
+        tokio::spawn(async move { fetch_failing(&cache, &calls).await })
+    };
+
+    assert!(leader.await.unwrap().is_err());
+    // Ожидающий ещё может держать неудачный запрос, но новый вызов идёт в Kodik сам
+    assert_eq!(fetch_counted(&cache, &calls, "fresh").await.unwrap(), "fresh");
+    assert!(waiter.await.unwrap().is_err());
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn disk_cache_survives_restart() {
+    let dir = std::env::temp_dir().join(format!("kamiview-cache-test-{}", std::process::id()));
+    let config = CacheConfig {
+        disk_dir: Some(dir.clone()),
+        ..memory_only(Duration::from_secs(60))
+    };
+    let calls = AtomicUsize::new(0);
+
+    let cache = ResponseCache::new(config.clone());
+    fetch_counted(&cache, &calls, "stored").await.unwrap();
+
+    let restarted = ResponseCache::new(config);
+    assert_eq!(fetch_counted(&restarted, &calls, "fetched").await.unwrap(), "stored");
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    let _ = std::fs::remove_dir_all(dir);
+}
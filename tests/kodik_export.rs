@@ -0,0 +1,121 @@
+use std::path::Path;
+use kami_view::kodik::{Playlist, PlaylistEntry, PlaylistFormat, PlaylistHeaders};
+
+const TITLE: &str = "Tom & Jerry <\"Tales\">, 'Vol'";
+
+fn entry(episode: i32, name: &str, url: Option<&str>, duration: Option<u32>, error: Option<&str>) -> PlaylistEntry {
+    PlaylistEntry {
+        season: 1,
+        episode,
+        name: name.to_string(),
+        url: url.map(str::to_string),
+        quality: url.map(|_| 720),
+        duration,
+        error: error.map(str::to_string),
+    }
+}
+
+fn playlist() -> Playlist {
+    Playlist {
+        title: TITLE.to_string(),
+        entries: vec![
+            entry(1, "Episode 1", Some("https://cdn.example/1/720.mp4:hls:manifest.m3u8?a=1&b=2"), Some(1440), None),
+            entry(2, "Episode 2", None, None, Some("Kodik\nis down")),
+            entry(3, "Серия 3, часть 1", Some("https://cdn.example/3/720.mp4"), None, None),
+        ],
+        headers: PlaylistHeaders {
+            user_agent: Some("KamiView".to_string()),
+            referer: None,
+        },
+    }
+}
+
+#[test]
+fn m3u8_export() {
+    assert_eq!(playlist().export(PlaylistFormat::M3u8), "\
+#EXTM3U
+#PLAYLIST:Tom & Jerry <\"Tales\">, 'Vol'
+#EXTINF:1440,Tom & Jerry <\"Tales\">, 'Vol' - Episode 1
+#EXTVLCOPT:http-user-agent=KamiView
+https://cdn.example/1/720.mp4:hls:manifest.m3u8?a=1&b=2
+# Episode 2: failed to resolve (Kodik is down)
+#EXTINF:-1,Tom & Jerry <\"Tales\">, 'Vol' - Серия 3, часть 1
+#EXTVLCOPT:http-user-agent=KamiView
+https://cdn.example/3/720.mp4
+");
+}
+
+#[test]
+fn m3u8_title_cannot_add_entries() {
+    let mut playlist = playlist();
+    playlist.title = "Title\n#EXTINF:-1,Injected\nhttp://evil.example/".to_string();
+
+    let exported = playlist.export(PlaylistFormat::M3u8);
+    assert!(!exported.lines().any(|line| line == "http://evil.example/"));
+    assert_eq!(exported.lines().filter(|line| line.starts_with("#EXTINF")).count(), 2);
+}
+
+#[test]
+fn xspf_export() {
+    assert_eq!(playlist().export(PlaylistFormat::Xspf), "\
+<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<playlist version=\"1\" xmlns=\"http://xspf.org/ns/0/\" xmlns:vlc=\"http://www.videolan.org/vlc/playlist/ns/0/\">
+  <title>Tom &amp; Jerry &lt;&quot;Tales&quot;&gt;, &apos;Vol&apos;</title>
+  <trackList>
+    <track>
+      <location>https://cdn.example/1/720.mp4:hls:manifest.m3u8?a=1&amp;b=2</location>
+      <title>Episode 1</title>
+      <album>Tom &amp; Jerry &lt;&quot;Tales&quot;&gt;, &apos;Vol&apos;</album>
+      <duration>1440000</duration>
+      <extension application=\"http://www.videolan.org/vlc/playlist/0\">
+        <vlc:option>http-user-agent=KamiView</vlc:option>
+      </extension>
+    </track>
+    <track>
+      <location>https://cdn.example/3/720.mp4</location>
+      <title>Серия 3, часть 1</title>
+      <album>Tom &amp; Jerry &lt;&quot;Tales&quot;&gt;, &apos;Vol&apos;</album>
+      <extension application=\"http://www.videolan.org/vlc/playlist/0\">
+        <vlc:option>http-user-agent=KamiView</vlc:option>
+      </extension>
+    </track>
+  </trackList>
+</playlist>
+");
+}
+
+#[test]
+fn pls_export() {
+    assert_eq!(playlist().export(PlaylistFormat::Pls), "\
+[playlist]
+File1=https://cdn.example/1/720.mp4:hls:manifest.m3u8?a=1&b=2
+Title1=Tom & Jerry <\"Tales\">, 'Vol' - Episode 1
+Length1=1440
+File2=https://cdn.example/3/720.mp4
+Title2=Tom & Jerry <\"Tales\">, 'Vol' - Серия 3, часть 1
+Length2=-1
+NumberOfEntries=2
+Version=2
+");
+}
+
+#[test]
+fn edl_export() {
+    // Длина в EDL считается в байтах, поэтому запятые и кириллица не ломают разбор
+    assert_eq!(playlist().export(PlaylistFormat::Edl), "\
+# mpv EDL v0
+%55%https://cdn.example/1/720.mp4:hls:manifest.m3u8?a=1&b=2,title=%9%Episode 1,length=1440
+%29%https://cdn.example/3/720.mp4,title=%26%Серия 3, часть 1
+");
+}
+
+#[test]
+fn playlist_format_names() {
+    assert_eq!(PlaylistFormat::from_path(Path::new("Naruto.M3U")), Some(PlaylistFormat::M3u8));
+    assert_eq!(PlaylistFormat::from_path(Path::new("naruto.xspf")), Some(PlaylistFormat::Xspf));
+    assert_eq!(PlaylistFormat::from_path(Path::new("naruto.wpl")), None);
+    assert_eq!(PlaylistFormat::from_path(Path::new("naruto")), None);
+
+    assert_eq!(serde_json::from_value::<PlaylistFormat>(serde_json::json!("edl")).unwrap(), PlaylistFormat::Edl);
+    assert!(serde_json::from_value::<PlaylistFormat>(serde_json::json!("wpl")).is_err());
+}
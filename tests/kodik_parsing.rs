@@ -0,0 +1,276 @@
+use kami_view::kodik::parsing::{self, PlayerKind};
+use kami_view::kodik::{InfoResponse, KodikError, MediaId, MediaResult, Season, TranslationKind};
+
+const SERIAL_PLAYER: &str = include_str!("fixtures/kodik/serial_player.html");
+const SERIAL_SEASON: &str = include_str!("fixtures/kodik/serial_season.html");
+const MOVIE_PLAYER: &str = include_str!("fixtures/kodik/movie_player.html");
+const APP_PLAYER: &str = include_str!("fixtures/kodik/app_player.js");
+const FTOR_RESPONSE: &str = include_str!("fixtures/kodik/ftor_response.json");
+const SEARCH_NULL_FIELDS: &str = include_str!("fixtures/kodik/search_null_fields.json");
+const SEARCH_GROUPING: &str = include_str!("fixtures/kodik/search_grouping.json");
+
+#[test]
+fn player_kind_from_link() {
+    assert_eq!(PlayerKind::from_link("https://kodik.info/serial/42371/3c9a1f5e7b2d/720p"), Some(PlayerKind::Serial));
+    assert_eq!(PlayerKind::from_link("https://kodik.info/video/91544/8e1f4a7b2c5d/720p"), Some(PlayerKind::Video));
+    assert_eq!(PlayerKind::from_link("https://kodik.info/season/1/abc/720p"), Some(PlayerKind::Serial));
+    assert_eq!(PlayerKind::from_link("https://example.com/"), None);
+}
+
+#[test]
+fn serial_player_page() {
+    let page = parsing::parse_player_page(SERIAL_PLAYER, PlayerKind::Serial);
+
+    assert_eq!(page.seasons, vec![1, 2]);
+    assert_eq!(page.selected_season, Some(1));
+    assert_eq!(page.selected_translation.as_deref(), Some("610"));
+
+    let numbers: Vec<_> = page.episodes.iter().map(|e| e.number).collect();
+    assert_eq!(numbers, vec![1, 2, 3]);
+    assert_eq!(page.episodes[1].media_id.as_deref(), Some("1187403"));
+    assert_eq!(page.episodes[1].hash.as_deref(), Some("c8e2d6b1f4a05c3e9d7b"));
+    assert_eq!(
+        page.episodes[1].page_url().as_deref(),
+        Some("https://kodik.info/seria/1187403/c8e2d6b1f4a05c3e9d7b/720p?min_age=16&first_url=false")
+    );
+
+    let ids: Vec<_> = page.translations.iter().map(|t| t.id.as_str()).collect();
+    assert_eq!(ids, vec!["610", "609", "869"]);
+    assert_eq!(page.translations[0].name, "AniLibria.TV (3 эп.)");
+    assert_eq!(page.translations[0].translation_type, TranslationKind::Voice);
+    assert_eq!(page.translations[2].translation_type, TranslationKind::Subtitles);
+}
+
+#[test]
+fn serial_season_page() {
+    let season = Season {
+        number: 2,
+        episodes: parsing::parse_episodes(SERIAL_SEASON),
+    };
+
+    let numbers: Vec<_> = season.episodes.iter().map(|e| e.number).collect();
+    assert_eq!(numbers, vec![1, 2, 4]);
+    assert_eq!(season.episode(4).and_then(|e| e.media_id.as_deref()), Some("1201153"));
+    assert_eq!(season.missing_episodes(), vec![3]);
+}
+
+#[test]
+fn season_starting_above_first_episode() {
+    let html = r#"<div class="serial-series-box"><select name="series">
+        <option value="13" data-id="1301" data-hash="aa13">13 серия</option>
+        <option value="14">14 серия</option>
+        <option value="16" data-id="1303" data-hash="aa16">16 серия</option>
+    </select></div>"#;
+    let season = Season {
+        number: 2,
+        episodes: parsing::parse_episodes(html),
+    };
+
+    let numbers: Vec<_> = season.episodes.iter().map(|e| e.number).collect();
+    assert_eq!(numbers, vec![13, 14, 16]);
+    assert_eq!(season.missing_episodes(), vec![15]);
+
+    // Без data-id/data-hash серия остаётся, но прямой ссылки на неё нет
+    let episode = season.episode(14).unwrap();
+    assert_eq!(episode.media_id, None);
+    assert_eq!(episode.page_url(), None);
+}
+
+#[test]
+fn next_episode() {
+    let info = InfoResponse {
+        series_count: 6,
+        translations: Vec::new(),
+        seasons: vec![
+            Season { number: 1, episodes: parsing::parse_player_page(SERIAL_PLAYER, PlayerKind::Serial).episodes },
+            Season { number: 2, episodes: parsing::parse_episodes(SERIAL_SEASON) },
+        ],
+        episodes_translation: Some("610".to_string()),
+    };
+
+    assert_eq!(info.next_episode(1, 1), Some((1, 2)));
+    assert_eq!(info.next_episode(1, 3), Some((2, 1)));
+    assert_eq!(info.next_episode(2, 2), Some((2, 4)));
+    assert_eq!(info.next_episode(2, 4), None);
+}
+
+#[test]
+fn movie_player_page() {
+    let page = parsing::parse_player_page(MOVIE_PLAYER, PlayerKind::Video);
+
+    assert!(page.seasons.is_empty());
+    assert!(page.episodes.is_empty());
+    assert_eq!(page.translations.len(), 2);
+    assert_eq!(page.translations[1].id, "1002");
+}
+
+#[test]
+fn page_without_translations_box() {
+    let page = parsing::parse_player_page(SERIAL_SEASON, PlayerKind::Serial);
+
+    assert_eq!(page.translations.len(), 1);
+    assert_eq!(page.translations[0].id, "0");
+    assert_eq!(page.translations[0].translation_type, TranslationKind::Unknown);
+}
+
+#[test]
+fn find_translation_media() {
+    let media = parsing::find_translation(SERIAL_PLAYER, PlayerKind::Serial, "609").unwrap();
+    assert_eq!(media.id, "42388");
+    assert_eq!(media.hash, "4dab2e6f8c3e");
+
+    let media = parsing::find_translation(MOVIE_PLAYER, PlayerKind::Video, "1291").unwrap();
+    assert_eq!(media.id, "91544");
+
+    assert!(matches!(
+        parsing::find_translation(SERIAL_PLAYER, PlayerKind::Serial, "1"),
+        Err(KodikError::NoResults(_))
+    ));
+    assert!(matches!(
+        parsing::find_translation(SERIAL_PLAYER, PlayerKind::Video, "610"),
+        Err(KodikError::LayoutChanged { .. })
+    ));
+}
+
+#[test]
+fn video_info_script() {
+    let info = parsing::parse_video_info(SERIAL_PLAYER).unwrap();
+    assert_eq!(info.video_type, "seria");
+    assert_eq!(info.hash, "b7d1c5a0e3f94b2d8c6a");
+    assert_eq!(info.id, "1187402");
+
+    let info = parsing::parse_video_info(MOVIE_PLAYER).unwrap();
+    assert_eq!(info.video_type, "video");
+    assert_eq!(info.media_ref().id, "91544");
+
+    match parsing::parse_video_info(SERIAL_SEASON) {
+        Err(KodikError::LayoutChanged { stage, snapshot, .. }) => {
+            assert_eq!(stage, "video_info");
+            assert!(snapshot.is_none());
+        },
+        other => panic!("expected LayoutChanged, got {:?}", other),
+    }
+}
+
+#[test]
+fn url_params() {
+    let params = parsing::parse_url_params(SERIAL_PLAYER).unwrap();
+    assert_eq!(params["d"], "kodik.info");
+    assert_eq!(params["d_sign"], "0f1e2d3c4b5a69788796a5b4c3d2e1f0");
+    assert_eq!(params["ref"], "");
+    assert_eq!(params["min_age"], "16");
+    assert_eq!(params["advert_debug"], "false");
+
+    assert!(parsing::parse_url_params(SERIAL_SEASON).is_err());
+}
+
+#[test]
+fn player_script_url() {
+    assert_eq!(parsing::parse_player_script_url(SERIAL_PLAYER).unwrap(), "/assets/js/app.serial.a4b8c15d.js");
+    assert_eq!(parsing::parse_player_script_url(MOVIE_PLAYER).unwrap(), "/assets/js/app.player_single.7e3d9b21.js");
+}
+
+#[test]
+fn post_link() {
+    assert_eq!(parsing::parse_post_link(APP_PLAYER).unwrap(), "/ftor");
+    assert!(parsing::parse_post_link("function(){}").is_err());
+}
+
+#[test]
+fn stream_links() {
+    let response: serde_json::Value = serde_json::from_str(FTOR_RESPONSE).unwrap();
+    let stream = parsing::parse_stream_links(&response).unwrap();
+
+    let qualities: Vec<_> = stream.qualities.iter().map(|q| q.quality).collect();
+    assert_eq!(qualities, vec![360, 480, 720]);
+    assert_eq!(stream.max_quality(), 720);
+    assert_eq!(stream.expires_hint.as_deref(), Some("2024011418"));
+
+    let best = stream.select(None).unwrap();
+    assert_eq!(
+        best.hls_url,
+        "https://cloud.kodik-storage.com/useruploads/3f2e1d0c-5b4a-4c3d-8e9f-0a1b2c3d4e5f/d41d8cd98f00b204e9800998ecf8427e:2024011418/720.mp4:hls:manifest.m3u8"
+    );
+    assert_eq!(
+        best.mp4_url,
+        "https://cloud.kodik-storage.com/useruploads/3f2e1d0c-5b4a-4c3d-8e9f-0a1b2c3d4e5f/d41d8cd98f00b204e9800998ecf8427e:2024011418/720.mp4/"
+    );
+}
+
+#[test]
+fn stream_links_without_links() {
+    let response = serde_json::json!({ "links": {} });
+    assert!(parsing::parse_stream_links(&response).is_err());
+}
+
+#[test]
+fn material_data_with_null_lists() {
+    let response: serde_json::Value = serde_json::from_str(SEARCH_NULL_FIELDS).unwrap();
+    let results: Vec<MediaResult> = serde_json::from_value(response["results"].clone()).unwrap();
+
+    let material = results[0].material_data.as_ref().unwrap();
+    assert!(material.other_titles.is_empty());
+    assert!(material.screenshots.is_empty());
+    assert!(material.genres.is_empty());
+    assert_eq!(material.other_titles_jp, vec!["俺だけレベルアップな件"]);
+    assert_eq!(material.genres(), ["Экшен", "Приключения", "Фэнтези"]);
+    assert_eq!(material.score(), Some(8.3));
+}
+
+#[test]
+fn list_cursor() {
+    // `next` не обязательно последний параметр
+    assert_eq!(
+        parsing::list_cursor("https://kodikapi.com/list?token=abc&next=c2Vh%3D%3D&types=anime-serial%2Canime&sort=updated_at&order=desc").as_deref(),
+        Some("c2Vh==")
+    );
+    assert_eq!(parsing::list_cursor("https://kodikapi.com/list?token=abc&sort=updated_at"), None);
+    assert_eq!(parsing::list_cursor("not a url"), None);
+}
+
+fn search_results(json: &str) -> Vec<MediaResult> {
+    let response: serde_json::Value = serde_json::from_str(json).unwrap();
+    serde_json::from_value(response["results"].clone()).unwrap()
+}
+
+#[test]
+fn group_results_by_title() {
+    let grouped = parsing::group_results(&search_results(SEARCH_GROUPING), true);
+
+    let ids: Vec<_> = grouped.iter().map(|r| r.media_id.clone()).collect();
+    assert_eq!(ids, vec![
+        Some(MediaId::Shikimori("52991".to_string())),
+        Some(MediaId::Shikimori("30276".to_string())),
+        Some(MediaId::Shikimori("34134".to_string())),
+        Some(MediaId::Kinopoisk("4510001".to_string())),
+        None,
+        None,
+    ]);
+
+    // Переводы одного тайтла собраны вместе, со своими числами серий
+    let frieren = &grouped[0];
+    let episodes: Vec<_> = frieren.translations.iter().map(|t| (t.id, t.episodes_count)).collect();
+    assert_eq!(episodes, vec![(610, Some(28)), (609, Some(26)), (869, Some(28))]);
+    assert_eq!(frieren.translations[1].updated_at.as_deref(), Some("2024-03-25T11:40:00Z"));
+    // Дата тайтла — самая свежая среди переводов, material_data берётся из любого
+    assert_eq!(frieren.updated_at.as_deref(), Some("2024-03-25T11:40:00Z"));
+    assert_eq!(frieren.material_data.as_ref().and_then(|m| m.duration), Some(24));
+
+    // Одинаковые названия с разными id остаются разными тайтлами
+    assert_eq!(grouped[1].title, grouped[2].title);
+    assert_eq!(grouped[1].translations.len(), 1);
+    assert_eq!(grouped[2].translations.len(), 1);
+
+    // Без shikimori_id тайтлы группируются по kinopoisk_id
+    let episodes: Vec<_> = grouped[3].translations.iter().map(|t| (t.id, t.episodes_count)).collect();
+    assert_eq!(episodes, vec![(767, Some(10)), (610, Some(8))]);
+    assert_eq!(grouped[3].updated_at.as_deref(), Some("2022-06-01T00:00:00Z"));
+}
+
+#[test]
+fn group_results_keeps_other_types() {
+    let grouped = parsing::group_results(&search_results(SEARCH_GROUPING), false);
+    assert_eq!(grouped.len(), 7);
+    assert!(grouped.iter().any(|r| r.media_id == Some(MediaId::Kinopoisk("716587".to_string()))));
+}
+
@@ -0,0 +1,48 @@
+use chrono::Datelike;
+use kami_view::kodik::{KodikError, SearchQuery};
+
+fn field(query: &SearchQuery, name: &str) -> Result<Option<String>, KodikError> {
+    query.filter_fields().map(|fields| {
+        fields.into_iter().find(|(field, _)| *field == name).map(|(_, value)| value)
+    })
+}
+
+#[test]
+fn search_query_years() {
+    let current = chrono::Local::now().year();
+    let years = |from: Option<i32>, to: Option<i32>| {
+        field(&SearchQuery { year_from: from, year_to: to, ..SearchQuery::default() }, "year")
+    };
+
+    assert_eq!(years(None, None).unwrap(), None);
+    assert_eq!(years(Some(2001), Some(2003)).unwrap().as_deref(), Some("2001,2002,2003"));
+    assert_eq!(years(Some(2003), Some(2001)).unwrap().as_deref(), Some("2001,2002,2003"));
+
+    // Только верхняя граница — все годы до неё, а не один
+    let until = years(None, Some(1903)).unwrap().unwrap();
+    assert_eq!(until, "1900,1901,1902,1903");
+
+    // Границы приводятся к 1900..=текущий год
+    let all = years(Some(0), None).unwrap().unwrap();
+    assert!(all.starts_with("1900,"));
+    assert!(all.ends_with(&current.to_string()));
+    assert_eq!(years(Some(2020), Some(current + 5)).unwrap().unwrap().split(',').count() as i32, current - 2020 + 1);
+
+    assert!(matches!(years(Some(current + 1), None), Err(KodikError::InvalidQuery(_))));
+    assert!(matches!(years(None, Some(1800)), Err(KodikError::InvalidQuery(_))));
+}
+
+#[test]
+fn search_query_min_rating() {
+    let rating = |min_rating: f32| {
+        field(&SearchQuery { min_rating: Some(min_rating), ..SearchQuery::default() }, "shikimori_rating")
+    };
+
+    assert_eq!(rating(7.5).unwrap().as_deref(), Some("7.5-10"));
+    assert_eq!(rating(0.0).unwrap().as_deref(), Some("0-10"));
+    assert_eq!(rating(10.0).unwrap().as_deref(), Some("10-10"));
+
+    for invalid in [-1.0, 10.5, f32::NAN, f32::INFINITY] {
+        assert!(matches!(rating(invalid), Err(KodikError::InvalidQuery(_))), "{} accepted", invalid);
+    }
+}
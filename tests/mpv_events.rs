@@ -0,0 +1,90 @@
+use kami_view::di::interfaces::MpvEvent;
+use kami_view::mpv::events::PlaybackTracker;
+use serde_json::{json, Value};
+
+fn time_pos(position: f64) -> Value {
+    json!({ "event": "property-change", "id": 1, "name": "time-pos", "data": position })
+}
+
+fn duration(duration: f64) -> Value {
+    json!({ "event": "property-change", "id": 2, "name": "duration", "data": duration })
+}
+
+fn eof_reached() -> Value {
+    json!({ "event": "property-change", "id": 3, "name": "eof-reached", "data": true })
+}
+
+fn end_file_error() -> Value {
+    json!({ "event": "end-file", "reason": "error", "file_error": "loading failed: 403" })
+}
+
+fn failure(event: Option<MpvEvent>) -> Option<(f64, String)> {
+    match event {
+        Some(MpvEvent::StreamFailed { position, reason }) => Some((position, reason)),
+        _ => None,
+    }
+}
+
+fn progress(event: Option<MpvEvent>) -> Option<f64> {
+    match event {
+        Some(MpvEvent::Progress { position, .. }) => Some(position),
+        _ => None,
+    }
+}
+
+#[test]
+fn premature_eof_is_a_failure() {
+    let mut tracker = PlaybackTracker::default();
+    tracker.handle(&duration(1440.0));
+    tracker.handle(&time_pos(600.0));
+
+    assert_eq!(failure(tracker.handle(&eof_reached())), Some((600.0, "stream ended early".to_string())));
+}
+
+#[test]
+fn eof_at_the_end_is_not_a_failure() {
+    let mut tracker = PlaybackTracker::default();
+    tracker.handle(&duration(1440.0));
+    tracker.handle(&time_pos(1437.5));
+
+    assert!(tracker.handle(&eof_reached()).is_none());
+}
+
+#[test]
+fn end_file_error_is_a_failure() {
+    let mut tracker = PlaybackTracker::default();
+    tracker.handle(&duration(1440.0));
+    tracker.handle(&time_pos(42.0));
+
+    assert_eq!(failure(tracker.handle(&end_file_error())), Some((42.0, "loading failed: 403".to_string())));
+
+    // Other end-file reasons are normal stops
+    let stop = json!({ "event": "end-file", "reason": "quit" });
+    assert!(tracker.handle(&stop).is_none());
+}
+
+#[test]
+fn no_failure_without_position() {
+    // A reload that fails before playing anything must not trigger another reload
+    let mut tracker = PlaybackTracker::default();
+    tracker.handle(&duration(1440.0));
+
+    assert!(tracker.handle(&end_file_error()).is_none());
+    assert!(tracker.handle(&eof_reached()).is_none());
+}
+
+#[test]
+fn progress_is_throttled() {
+    let mut tracker = PlaybackTracker::default();
+    // No duration yet: nothing to report
+    assert!(tracker.handle(&time_pos(0.5)).is_none());
+
+    tracker.handle(&duration(1440.0));
+    assert_eq!(progress(tracker.handle(&time_pos(1.0))), Some(1.0));
+    assert!(tracker.handle(&time_pos(3.0)).is_none());
+    assert!(tracker.handle(&time_pos(5.9)).is_none());
+    assert_eq!(progress(tracker.handle(&time_pos(6.0))), Some(6.0));
+
+    // Seeks back count as movement too
+    assert_eq!(progress(tracker.handle(&time_pos(0.0))), Some(0.0));
+}
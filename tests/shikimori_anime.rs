@@ -0,0 +1,65 @@
+use kami_view::shikimori::AnimeDetails;
+use serde_json::{json, Value};
+
+// Shaped after the public `/api/animes/5114` response, trimmed and with a shortened
+// description; keys AnimeDetails does not know are kept to check they are ignored
+const ANIME: &str = include_str!("fixtures/shikimori/anime_5114.json");
+
+fn with_score(score: Value) -> AnimeDetails {
+    let mut anime: Value = serde_json::from_str(ANIME).unwrap();
+    anime["score"] = score;
+    serde_json::from_value(anime).unwrap()
+}
+
+#[test]
+fn anime_details() {
+    let anime: AnimeDetails = serde_json::from_str(ANIME).unwrap();
+
+    assert_eq!(anime.id, 5114);
+    assert_eq!(anime.russian.as_deref(), Some("Стальной алхимик: Братство"));
+    assert_eq!(anime.english, vec![Some("Fullmetal Alchemist: Brotherhood".to_string())]);
+    assert_eq!(anime.synonyms.len(), 3);
+    assert_eq!(anime.kind.as_deref(), Some("tv"));
+    assert_eq!(anime.status.as_deref(), Some("released"));
+    assert_eq!((anime.episodes, anime.episodes_aired, anime.duration), (Some(64), Some(64), Some(24)));
+    assert_eq!(anime.next_episode_at, None);
+    assert_eq!(anime.image.original.as_deref(), Some("/system/animes/original/5114.jpg?1674378220"));
+
+    let genres: Vec<_> = anime.genres.iter().filter_map(|g| g.russian.as_deref()).collect();
+    assert_eq!(genres, vec!["Экшен", "Драма", "Военное"]);
+    assert_eq!(anime.studios[0].name, "Bones");
+}
+
+#[test]
+fn anime_score() {
+    let anime: AnimeDetails = serde_json::from_str(ANIME).unwrap();
+    assert_eq!(anime.score, Some(9.09));
+
+    assert_eq!(with_score(json!(8.75)).score, Some(8.75));
+    assert_eq!(with_score(json!(" 7.5 ")).score, Some(7.5));
+    // Unrated titles come back as "0.0"
+    assert_eq!(with_score(json!("0.0")).score, None);
+    assert_eq!(with_score(json!(0)).score, None);
+    assert_eq!(with_score(json!("")).score, None);
+    assert_eq!(with_score(Value::Null).score, None);
+
+    let mut anime: Value = serde_json::from_str(ANIME).unwrap();
+    anime.as_object_mut().unwrap().remove("score");
+    assert_eq!(serde_json::from_value::<AnimeDetails>(anime).unwrap().score, None);
+}
+
+#[test]
+fn anime_plain_description() {
+    let anime: AnimeDetails = serde_json::from_str(ANIME).unwrap();
+
+    assert_eq!(
+        anime.plain_description().as_deref(),
+        Some("Братья Эдвард и Альфонс Элрики пытаются вернуть утраченное с помощью философского камня.\r\nТекст под спойлером")
+    );
+
+    let mut anime = anime;
+    anime.description = Some("[character=11][/character]  ".to_string());
+    assert_eq!(anime.plain_description(), None);
+    anime.description = None;
+    assert_eq!(anime.plain_description(), None);
+}
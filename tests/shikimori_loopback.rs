@@ -0,0 +1,75 @@
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use kami_view::shikimori::LoopbackListener;
+use url::Url;
+
+/// Listener on a free port, waiting for the code on its own thread
+fn listen() -> (u16, String, JoinHandle<anyhow::Result<String>>) {
+    let listener = LoopbackListener::bind(0).unwrap();
+    let port = Url::parse(listener.redirect_uri()).unwrap().port().unwrap();
+    let state = listener.state().to_string();
+    let waiting = thread::spawn(move || listener.wait_for_code(Duration::from_secs(10)));
+    (port, state, waiting)
+}
+
+/// Plain HTTP GET; returns the response status
+fn get(port: u16, path: &str) -> u16 {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+    write!(stream, "GET {} HTTP/1.1\r\nHost: 127.0.0.1\r\nConnection: close\r\n\r\n", path).unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response.split_whitespace().nth(1).unwrap().parse().unwrap()
+}
+
+#[test]
+fn redirect_uri_points_at_the_callback() {
+    let listener = LoopbackListener::bind(0).unwrap();
+    let uri = Url::parse(listener.redirect_uri()).unwrap();
+
+    assert_eq!(uri.host_str(), Some("127.0.0.1"));
+    assert_ne!(uri.port(), Some(0));
+    assert_eq!(uri.path(), "/callback");
+}
+
+#[test]
+fn code_with_matching_state_is_returned() {
+    let (port, state, waiting) = listen();
+
+    // Browsers ask for the icon too; that and a foreign state don't end the wait
+    assert_eq!(get(port, "/favicon.ico"), 404);
+    assert_eq!(get(port, "/callback?code=stolen&state=other"), 400);
+    assert_eq!(get(port, &format!("/callback?code=abc%2B1&state={}", state)), 200);
+
+    assert_eq!(waiting.join().unwrap().unwrap(), "abc+1");
+}
+
+#[test]
+fn denied_authorization_is_an_error() {
+    let (port, state, waiting) = listen();
+
+    assert_eq!(get(port, &format!("/callback?error=access_denied&state={}", state)), 400);
+
+    let error = waiting.join().unwrap().unwrap_err();
+    assert!(error.to_string().contains("access_denied"));
+}
+
+#[test]
+fn redirect_without_code_is_an_error() {
+    let (port, state, waiting) = listen();
+
+    assert_eq!(get(port, &format!("/callback?state={}", state)), 400);
+
+    let error = waiting.join().unwrap().unwrap_err();
+    assert!(error.to_string().contains("no authorization code"));
+}
+
+#[test]
+fn wait_times_out() {
+    let listener = LoopbackListener::bind(0).unwrap();
+
+    let error = listener.wait_for_code(Duration::from_millis(100)).unwrap_err();
+    assert!(error.to_string().contains("Timed out"));
+}
@@ -0,0 +1,63 @@
+use kami_view::shikimori::{RateChange, RateStatus, RatesData, UserRate};
+
+fn change(target_id: i64, status: Option<RateStatus>, episodes: Option<i32>, score: Option<i32>, changed_at: i64) -> RateChange {
+    RateChange { target_id, status, episodes, score, changed_at }
+}
+
+fn remote(target_id: i64, updated_at: &str) -> UserRate {
+    UserRate {
+        id: Some(1),
+        target_id,
+        status: RateStatus::Watching,
+        episodes: 3,
+        score: 0,
+        rewatches: 0,
+        updated_at: Some(updated_at.to_string()),
+    }
+}
+
+#[test]
+fn merge_keeps_unset_fields() {
+    let mut queued = change(5114, Some(RateStatus::Watching), Some(3), None, 100);
+    queued.merge(&change(5114, None, Some(4), Some(9), 200));
+
+    assert_eq!(queued, change(5114, Some(RateStatus::Watching), Some(4), Some(9), 200));
+}
+
+#[test]
+fn queue_merges_edits_of_one_title() {
+    let mut data = RatesData::default();
+
+    let rate = data.queue(change(5114, Some(RateStatus::Watching), Some(1), None, 1_700_000_000));
+    assert_eq!(rate.id, None);
+    assert_eq!((rate.status, rate.episodes), (RateStatus::Watching, 1));
+    assert_eq!(rate.updated_timestamp(), 1_700_000_000);
+
+    data.queue(change(5114, None, Some(2), None, 1_700_000_100));
+    data.queue(change(1535, Some(RateStatus::Planned), None, None, 1_700_000_200));
+
+    assert_eq!(data.pending.len(), 2);
+    assert_eq!(data.pending[0], change(5114, Some(RateStatus::Watching), Some(2), None, 1_700_000_100));
+    assert_eq!(data.rates.len(), 2);
+    let rate = data.rate(5114).unwrap();
+    assert_eq!((rate.status, rate.episodes), (RateStatus::Watching, 2));
+    assert_eq!(rate.updated_timestamp(), 1_700_000_100);
+}
+
+#[test]
+fn newest_edit_wins() {
+    // 2024-01-01T00:00:00Z
+    let remote = remote(5114, "2024-01-01T03:00:00.000+03:00");
+    assert_eq!(remote.updated_timestamp(), 1_704_067_200);
+
+    assert!(change(5114, None, Some(4), None, 1_704_067_199).is_older_than(&remote));
+    // A tie goes to the local edit
+    assert!(!change(5114, None, Some(4), None, 1_704_067_200).is_older_than(&remote));
+    assert!(!change(5114, None, Some(4), None, 1_704_067_201).is_older_than(&remote));
+
+    // A rate without a readable `updated_at` never wins
+    let mut undated = remote.clone();
+    undated.updated_at = Some("yesterday".to_string());
+    assert_eq!(undated.updated_timestamp(), 0);
+    assert!(!change(5114, None, Some(4), None, 1).is_older_than(&undated));
+}
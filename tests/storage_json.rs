@@ -0,0 +1,52 @@
+use std::collections::BTreeMap;
+use parking_lot::Mutex;
+use kami_view::storage::{read_json, update_json};
+
+type Counters = BTreeMap<String, u32>;
+
+#[test]
+fn missing_file_starts_empty() {
+    let path = std::env::temp_dir().join(format!("kamiview-json-missing-{}", std::process::id())).join("counters.json");
+    let lock = Mutex::new(());
+
+    assert!(read_json::<Counters>(&path).unwrap().is_empty());
+
+    // Каталог создаётся при первой записи
+    update_json(&path, &lock, |data: &mut Counters| data.insert("a".to_string(), 1)).unwrap();
+    assert_eq!(read_json::<Counters>(&path).unwrap()["a"], 1);
+
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn unreadable_file_is_kept() {
+    let path = std::env::temp_dir().join(format!("kamiview-json-unreadable-{}.json", std::process::id()));
+    std::fs::write(&path, "{ \"a\": ").unwrap();
+    let lock = Mutex::new(());
+
+    assert!(read_json::<Counters>(&path).is_err());
+    let mut changed = false;
+    assert!(update_json(&path, &lock, |_: &mut Counters| changed = true).is_err());
+    assert!(!changed);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "{ \"a\": ");
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn update_returns_the_change_result() {
+    let path = std::env::temp_dir().join(format!("kamiview-json-update-{}.json", std::process::id()));
+    let lock = Mutex::new(());
+
+    for expected in 1..=3 {
+        let count = update_json(&path, &lock, |data: &mut Counters| {
+            let count = data.entry("a".to_string()).or_insert(0);
+            *count += 1;
+            *count
+        }).unwrap();
+        assert_eq!(count, expected);
+    }
+    assert!(!path.with_extension("json.tmp").exists());
+
+    std::fs::remove_file(&path).unwrap();
+}